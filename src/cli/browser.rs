@@ -0,0 +1,89 @@
+// Opening a URL in a browser: `browser` in shtml.toml, `$BROWSER` or the system opener.
+
+use crate::config;
+use std::{
+    env, fs, io,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Openers exit right away; a browser started directly keeps running, which
+// counts as success once this has passed
+const EXIT_WAIT: Duration = Duration::from_secs(2);
+
+pub fn open(url: &str) -> io::Result<()> {
+    let commands = match (&config::get().dev.browser, env::var("BROWSER")) {
+        (Some(browser), _) => vec![browser.clone()],
+        (None, Ok(browser)) if !browser.trim().is_empty() => {
+            browser.split(':').map(str::to_string).collect()
+        }
+        _ => platform_openers(),
+    };
+
+    let mut errors = Vec::new();
+    for command in &commands {
+        match run(command, url) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{}: {}", command.trim(), e)),
+        }
+    }
+    Err(io::Error::other(format!(
+        "{} (set $BROWSER or `browser` in {})",
+        errors.join("; "),
+        config::CONFIG_FILE
+    )))
+}
+
+fn platform_openers() -> Vec<String> {
+    if cfg!(target_os = "macos") {
+        vec!["open".into()]
+    } else if cfg!(windows) {
+        // `start` is a cmd builtin; the empty argument is the window title
+        vec!["cmd /C start \"\"".into()]
+    } else if is_wsl() {
+        vec!["wslview".into(), "xdg-open".into()]
+    } else {
+        vec!["xdg-open".into()]
+    }
+}
+
+fn is_wsl() -> bool {
+    env::var_os("WSL_DISTRO_NAME").is_some()
+        || fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+}
+
+fn run(command: &str, url: &str) -> io::Result<()> {
+    let mut words: Vec<String> = command
+        .split_whitespace()
+        .map(|word| word.trim_matches('"').replace("%s", url))
+        .collect();
+    if !command.contains("%s") {
+        words.push(url.to_string());
+    }
+    let Some((program, args)) = words.split_first() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command"));
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let started = Instant::now();
+    while started.elapsed() < EXIT_WAIT {
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(io::Error::other(format!("exited with {}", status)))
+            };
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    // Still running: the browser itself. Reap it whenever it exits.
+    thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use std::{fs, io, process::Command, time::Instant};
+use crate::host_files;
+use std::{fs, io, path::Path, process::Command, time::Instant};
 
 pub fn run(output: &str) -> io::Result<()> {
     println!("╔══════════════════════════════════════╗");
@@ -32,8 +33,7 @@ pub fn run(output: &str) -> io::Result<()> {
         eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
         eprintln!("{}", String::from_utf8_lossy(&build_output.stderr));
         eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
+        return Err(io::Error::other(
             "Swift build failed"
         ));
     }
@@ -61,8 +61,7 @@ pub fn run(output: &str) -> io::Result<()> {
         eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
         eprintln!("{}", String::from_utf8_lossy(&run_output.stderr));
         eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
+        return Err(io::Error::other(
             "Failed to generate HTML"
         ));
     }
@@ -86,6 +85,18 @@ pub fn run(output: &str) -> io::Result<()> {
         println!("   ✅ Assets copied in {:.2}s", assets_time.as_secs_f64());
     }
 
+    // Netlify-style host files are checked here rather than after deploy
+    for name in [host_files::HEADERS_FILE, host_files::REDIRECTS_FILE] {
+        if !Path::new(name).exists() {
+            continue;
+        }
+        println!("\n📄 Checking {}...", name);
+        check_host_file(name)?;
+        fs::create_dir_all(output)?;
+        fs::copy(name, Path::new(output).join(name))?;
+        println!("   ✅ Copied to {}/{}", output, name);
+    }
+
     // Show results
     let total_time = total_start.elapsed();
     println!("\n╔══════════════════════════════════════╗");
@@ -113,7 +124,31 @@ pub fn run(output: &str) -> io::Result<()> {
     Ok(())
 }
 
-fn extract_executable_name(package_swift: &str) -> Option<String> {
+fn check_host_file(name: &str) -> io::Result<()> {
+    let text = fs::read_to_string(name)?;
+    let (rules, problems) = if name == host_files::HEADERS_FILE {
+        let (rules, problems) = host_files::parse_headers(&text);
+        (rules.len(), problems)
+    } else {
+        let (rules, problems) = host_files::parse_redirects(&text);
+        (rules.len(), problems)
+    };
+
+    for problem in &problems {
+        let icon = if problem.is_error { "❌" } else { "⚠️ " };
+        eprintln!("   {} {}:{}: {}", icon, name, problem.line, problem.message);
+    }
+    if problems.iter().any(|p| p.is_error) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} has errors", name),
+        ));
+    }
+    println!("   ✅ {} rule(s)", rules);
+    Ok(())
+}
+
+pub fn extract_executable_name(package_swift: &str) -> Option<String> {
     // Simple parser to find .executableTarget name
     for line in package_swift.lines() {
         if line.contains(".executableTarget") {
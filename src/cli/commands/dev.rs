@@ -1,9 +1,150 @@
+use super::build;
+use crate::browser;
+use crate::dev_events::{self, BuildStep, DevEvent};
+use crate::network::Address;
 use crate::server;
-use crate::tui::{AppState, BuildState};
-use std::{io, sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}}};
+use crate::tui::AppState;
+use std::{fs, io::{self, IsTerminal, Write}, net::IpAddr, sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}}, thread};
 
-pub fn run(port: u16) -> io::Result<()> {
-    let app_state = Arc::new(Mutex::new(AppState::new(port)));
+pub struct Options {
+    pub port: u16,
+    pub host: String,
+    pub strict_port: bool,
+    pub https: bool,
+    pub mocks: Option<String>,
+    /// An empty token asks for a generated one
+    pub token: Option<String>,
+    pub allow: Vec<String>,
+    /// Open a browser after the first successful build
+    pub open: bool,
+    pub no_tui: bool,
+    /// Print events as JSON lines instead of a TUI or logs
+    pub json: bool,
+}
+
+pub fn run(options: Options) -> io::Result<()> {
+    let Options {
+        port,
+        host,
+        strict_port,
+        https,
+        mocks,
+        token,
+        allow,
+        open,
+        no_tui,
+        json,
+    } = options;
+    crate::config::load()?;
+
+    if let Some(dir) = mocks {
+        if !std::path::Path::new(&dir).is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("mocks directory '{}' not found", dir),
+            ));
+        }
+        server::mocks::enable(dir);
+    }
+
+    let allow = allow
+        .iter()
+        .map(|cidr| cidr.parse::<server::access::Cidr>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let token = token.map(|token| {
+        if token.is_empty() {
+            server::access::generate_token()
+        } else {
+            token
+        }
+    });
+    if token.is_some() || !allow.is_empty() {
+        server::access::enable(token.clone(), allow);
+    }
+
+    // Bound here rather than in the server thread, so a failure is reported
+    // before the TUI starts and the TUI shows the port actually used
+    let listener = server::bind(&host, port, strict_port)?;
+    let mut state = AppState::new(listener.local_addr()?, port, https);
+    state.token = token;
+    state.open_after_build = open;
+    if let Some(name) = project_name() {
+        let ips: Vec<IpAddr> = state
+            .addresses
+            .iter()
+            .filter_map(|address| address.name.parse().ok())
+            .filter(|ip: &IpAddr| !ip.is_loopback())
+            .collect();
+        if !ips.is_empty() {
+            match server::mdns::advertise(&name, state.port, https, &ips) {
+                Ok(host) => state.addresses.push(Address::new("DNS-SD", host)),
+                Err(e) => state.notice = Some(format!("mDNS advertisement failed: {}", e)),
+            }
+        }
+    }
+    let tls = if https {
+        // Every address the QR code can show has to be in the certificate
+        let mut names = vec![
+            "localhost".to_string(),
+            "127.0.0.1".to_string(),
+            "::1".to_string(),
+        ];
+        for address in &state.addresses {
+            if !names.contains(&address.name) {
+                names.push(address.name.clone());
+            }
+        }
+        let tls = server::tls::setup(&names)?;
+        // Shown again once the TUI exits; the TUI itself keeps the CA path.
+        // JSON output has it in `server-ready`.
+        if !json {
+            server::tls::print_trust_instructions(&tls.ca_path, &state.server_url);
+        }
+        state.ca_path = Some(tls.ca_path.display().to_string());
+        Some(tls.config)
+    } else {
+        None
+    };
+
+    if json {
+        dev_events::subscribe(|event| {
+            // Nobody to tell when the reader went away
+            let _ = writeln!(io::stdout().lock(), "{}", dev_events::to_json(event));
+        });
+        if open {
+            open_after_first_build(state.browser_url());
+        }
+        dev_events::emit(state.ready_event());
+        return server::start_server(listener, tls);
+    }
+
+    // CI, pipes and SSH sessions without a terminal get plain logs
+    if no_tui || !io::stdout().is_terminal() {
+        print_urls(&state);
+        dev_events::subscribe(log_event);
+        if open {
+            open_after_first_build(state.browser_url());
+        }
+        dev_events::emit(state.ready_event());
+        log(&format!("🌐 Listening on {} (Ctrl+C to stop)", listener.local_addr()?));
+        let result = server::start_server(listener, tls);
+        log("👋 Stopped");
+        return result;
+    }
+
+    let ready = state.ready_event();
+    let app_state = Arc::new(Mutex::new(state));
+    let events_state = app_state.clone();
+    dev_events::subscribe(move |event| {
+        let mut state = events_state.lock().unwrap();
+        state.apply(event);
+        if matches!(event, DevEvent::BuildFinished { success: true, .. }) && state.open_after_build {
+            state.open_after_build = false;
+            crate::tui::open_browser(&events_state);
+        }
+    });
+    dev_events::emit(ready);
     let rebuild_flag = Arc::new(AtomicBool::new(false));
     
     // Clone for trigger function
@@ -13,10 +154,9 @@ pub fn run(port: u16) -> io::Result<()> {
     });
     
     // Start server in background
-    let app_state_clone = app_state.clone();
     let rebuild_flag_clone = rebuild_flag.clone();
-    std::thread::spawn(move || {
-        if let Err(e) = server::start_server_with_tui(port, app_state_clone, rebuild_flag_clone) {
+    let server_thread = std::thread::spawn(move || {
+        if let Err(e) = server::start_server_with_tui(listener, tls, rebuild_flag_clone) {
             eprintln!("Server error: {}", e);
         }
     });
@@ -25,7 +165,79 @@ pub fn run(port: u16) -> io::Result<()> {
     std::thread::sleep(std::time::Duration::from_millis(500));
     
     // Run TUI
-    crate::tui::run_tui(app_state, trigger_rebuild)?;
-    
-    Ok(())
+    let result = crate::tui::run_tui(app_state, trigger_rebuild);
+
+    server::shutdown();
+    let _ = server_thread.join();
+    result
+}
+
+// The executable target's name, which is what the site is called
+fn project_name() -> Option<String> {
+    let package = fs::read_to_string("Package.swift").ok()?;
+    build::extract_executable_name(&package)
+}
+
+fn print_urls(state: &AppState) {
+    println!("⚡ SHTML dev server");
+    println!("   Local:   {}", state.browser_url());
+    for address in &state.addresses {
+        println!("   Network: {}  ({})", state.network_url(address), address.interface);
+    }
+    if let Some(notice) = &state.notice {
+        println!("   ⚠️  {}", notice);
+    }
+}
+
+// One timestamped line, so logs read well in CI and when piped
+fn log(message: &str) {
+    println!("[{}] {}", chrono::Local::now().format("%H:%M:%S"), message);
+}
+
+fn log_event(event: &DevEvent) {
+    match event {
+        DevEvent::FileChanged { path } => log(&format!("🔔 Changed: {}", path)),
+        DevEvent::BuildStart => log("🔨 Building..."),
+        DevEvent::BuildProgress { step: BuildStep::Generating } => log("✅ Compiled"),
+        DevEvent::BuildFinished {
+            success: true,
+            duration_ms,
+            size,
+            diagnostics,
+        } => {
+            if let Some(warning) = diagnostics {
+                log(&format!("⚠️  {}", warning));
+            }
+            let size = size.map_or_else(|| "no index.html".to_string(), dev_events::format_size);
+            log(&format!(
+                "✅ Build complete ({:.1}s, {})",
+                *duration_ms as f32 / 1000.0,
+                size
+            ));
+        }
+        DevEvent::BuildFinished { diagnostics, .. } => {
+            if let Some(diagnostics) = diagnostics {
+                eprintln!("{}", diagnostics);
+            }
+            log("❌ Build failed");
+        }
+        _ => {}
+    }
+}
+
+// `--open` without the TUI; a failure is only worth a warning
+fn open_after_first_build(url: String) {
+    let mut url = Some(url);
+    dev_events::subscribe(move |event| {
+        if let DevEvent::BuildFinished { success: true, .. } = event {
+            if let Some(url) = url.take() {
+                // Off the event path: a browser can take a while to start
+                thread::spawn(move || {
+                    if let Err(e) = browser::open(&url) {
+                        eprintln!("⚠️  Couldn't open a browser: {}", e);
+                    }
+                });
+            }
+        }
+    });
 }
@@ -2,7 +2,7 @@ use std::{
     env,
     fs,
     io::{self, Write},
-    path::{Path, PathBuf},
+    path::PathBuf,
 };
 
 pub fn run(name: Option<String>) -> io::Result<()> {
@@ -28,17 +28,16 @@ pub fn run(name: Option<String>) -> io::Result<()> {
     }
 
     // Allow "." as "current dir", but disallow other weird path-like names
-    if project_name != "." {
-        if project_name == ".."
+    if project_name != "."
+        && (project_name == ".."
             || project_name.contains(std::path::MAIN_SEPARATOR)
             || project_name.contains('/')
-            || project_name.contains('\\')
-        {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Project name must be a single directory name (no path separators, no '..')",
-            ));
-        }
+            || project_name.contains('\\'))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Project name must be a single directory name (no path separators, no '..')",
+        ));
     }
 
     // Decide target directory
@@ -0,0 +1,128 @@
+// Optional project configuration from `shtml.toml` next to Package.swift.
+
+use serde::Deserialize;
+use std::{collections::HashMap, fs, io, path::Path, sync::OnceLock};
+
+pub const CONFIG_FILE: &str = "shtml.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub dev: DevConfig,
+    /// Extra `extension = "content/type"` mappings for served files
+    pub mime: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DevConfig {
+    /// `Cache-Control` values for static files; the first matching rule wins
+    pub cache_control: Vec<CacheRule>,
+    /// Requests forwarded to another local server; the first matching rule wins
+    pub proxy: Vec<ProxyRule>,
+    /// Which unknown paths get the generated page instead of a 404
+    pub spa_fallback: SpaFallback,
+    /// Command that opens a URL; `%s` stands for it
+    pub browser: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpaFallback {
+    /// Every path that doesn't look like a file
+    #[default]
+    On,
+    Off,
+    /// Only paths matching a `Router` route (`data-path`) in the page
+    Routes,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CacheRule {
+    pub pattern: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyRule {
+    pub path: String,
+    /// `http://host:port`, optionally with a base path the request path is
+    /// appended to
+    pub target: String,
+}
+
+/// Reads `shtml.toml` (if there is one) and makes it available through `get`.
+pub fn load() -> io::Result<&'static Config> {
+    let config = if Path::new(CONFIG_FILE).exists() {
+        let content = fs::read_to_string(CONFIG_FILE)?;
+        toml::from_str(&content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", CONFIG_FILE, e),
+            )
+        })?
+    } else {
+        Config::default()
+    };
+    validate(&config)?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+fn validate(config: &Config) -> io::Result<()> {
+    for rule in &config.dev.proxy {
+        if !rule.target.starts_with("http://") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: proxy target {:?} must be an http:// URL",
+                    CONFIG_FILE, rule.target
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Matches a URL path against a pattern where `*` stands for any run of
+/// characters (including '/'). Patterns without a leading '/' only need to
+/// match the end of the path, so `*.mp4` applies in every directory.
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    if pattern.starts_with('/') {
+        wildcard_match(pattern.as_bytes(), path.as_bytes())
+    } else {
+        let mut anchored = Vec::with_capacity(pattern.len() + 2);
+        anchored.extend_from_slice(b"*/");
+        anchored.extend_from_slice(pattern.as_bytes());
+        wildcard_match(&anchored, path.as_bytes())
+    }
+}
+
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    // Iterative matcher with single-star backtracking
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
@@ -0,0 +1,102 @@
+// Events from `shtml dev`, presented by the TUI, the plain logs or `--json`.
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum DevEvent {
+    ServerReady {
+        local_url: String,
+        network: Vec<NetworkUrl>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ca_path: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        notice: Option<String>,
+    },
+    FileChanged {
+        path: String,
+    },
+    BuildStart,
+    BuildProgress {
+        step: BuildStep,
+    },
+    BuildFinished {
+        success: bool,
+        duration_ms: u64,
+        /// Size of the generated index.html
+        #[serde(skip_serializing_if = "Option::is_none")]
+        size: Option<u64>,
+        /// Compiler or generator output of a failed build, or a warning
+        /// about a successful one (assets that couldn't be copied)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        diagnostics: Option<String>,
+    },
+    /// A page opened the live-reload stream
+    ClientConnected {
+        address: String,
+        clients: usize,
+    },
+    Request {
+        method: String,
+        path: String,
+        status: u16,
+        duration_ms: u64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkUrl {
+    pub interface: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuildStep {
+    Compiling,
+    Generating,
+    CopyingAssets,
+}
+
+type Subscriber = Box<dyn FnMut(&DevEvent) + Send>;
+
+// Called in turn under the lock, so each sees events in one order
+static SUBSCRIBERS: Mutex<Vec<Subscriber>> = Mutex::new(Vec::new());
+
+pub fn subscribe(subscriber: impl FnMut(&DevEvent) + Send + 'static) {
+    SUBSCRIBERS.lock().unwrap().push(Box::new(subscriber));
+}
+
+/// Hands `event` to every subscriber. Subscribers must not emit.
+pub fn emit(event: DevEvent) {
+    for subscriber in SUBSCRIBERS.lock().unwrap().iter_mut() {
+        subscriber(&event);
+    }
+}
+
+/// `event` as one line of JSON, with the local time it was printed at.
+pub fn to_json(event: &DevEvent) -> String {
+    #[derive(Serialize)]
+    struct Line<'a> {
+        time: String,
+        #[serde(flatten)]
+        event: &'a DevEvent,
+    }
+
+    let line = Line {
+        time: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+        event,
+    };
+    serde_json::to_string(&line).expect("events serialize to JSON")
+}
+
+pub fn format_size(size: u64) -> String {
+    if size < 1024 {
+        format!("{} B", size)
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KB", size as f64 / 1024.0)
+    } else {
+        format!("{:.2} MB", size as f64 / (1024.0 * 1024.0))
+    }
+}
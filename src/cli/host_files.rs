@@ -0,0 +1,339 @@
+// Netlify-style `_headers` and `_redirects` files.
+
+use crate::server::url;
+use std::{collections::HashMap, fs, path::PathBuf};
+
+pub const HEADERS_FILE: &str = "_headers";
+pub const REDIRECTS_FILE: &str = "_redirects";
+
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    pub from: String,
+    pub to: String,
+    /// 3xx redirects; anything else is a rewrite served with that status
+    pub status: u16,
+    /// Applies even when a file exists at `from`
+    pub force: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct HeaderRule {
+    pub pattern: String,
+    pub headers: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+pub struct Problem {
+    pub line: usize,
+    pub message: String,
+    /// Lines with errors are skipped; warnings are applied as far as possible
+    pub is_error: bool,
+}
+
+impl Redirect {
+    pub fn is_redirect(&self) -> bool {
+        (300..400).contains(&self.status)
+    }
+
+    /// The target for `path`, with `:placeholders` and `:splat` filled in.
+    /// Captures are percent-encoded again, so the target is a URL.
+    pub fn target(&self, path: &str) -> Option<String> {
+        let captures = match_pattern(&self.from, path)?;
+        let mut target = self.to.clone();
+        // Longest names first, so `:id` doesn't clobber `:identity`
+        let mut names: Vec<&String> = captures.keys().collect();
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        for name in names {
+            target = target.replace(&format!(":{}", name), &url::encode_path(&captures[name]));
+        }
+        Some(target)
+    }
+}
+
+impl HeaderRule {
+    pub fn matches(&self, path: &str) -> bool {
+        match_pattern(&self.pattern, path).is_some()
+    }
+}
+
+/// The file to use: the project's own, or one already in `public/`.
+pub fn locate(name: &str) -> Option<PathBuf> {
+    [PathBuf::from(name), PathBuf::from("public").join(name)]
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+/// Rules from `_redirects`, skipping invalid lines.
+pub fn load_redirects() -> Vec<Redirect> {
+    locate(REDIRECTS_FILE)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|text| parse_redirects(&text).0)
+        .unwrap_or_default()
+}
+
+/// Rules from `_headers`, skipping invalid lines.
+pub fn load_headers() -> Vec<HeaderRule> {
+    locate(HEADERS_FILE)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|text| parse_headers(&text).0)
+        .unwrap_or_default()
+}
+
+pub fn parse_redirects(text: &str) -> (Vec<Redirect>, Vec<Problem>) {
+    let mut rules = Vec::new();
+    let mut problems = Vec::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let content = raw.split('#').next().unwrap_or("").trim();
+        if content.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = content.split_whitespace().collect();
+        if fields.len() < 2 {
+            problems.push(error(line, "expected `from to [status]`"));
+            continue;
+        }
+
+        let (from, to) = (fields[0], fields[1]);
+        if !from.starts_with('/') {
+            problems.push(error(line, &format!("`{}` must be a path starting with '/'", from)));
+            continue;
+        }
+        if let Some(problem) = check_pattern(from, line) {
+            problems.push(problem);
+            continue;
+        }
+        if !to.starts_with('/') && !to.starts_with("http://") && !to.starts_with("https://") {
+            problems.push(error(line, &format!("`{}` must be a path or an absolute URL", to)));
+            continue;
+        }
+
+        let mut status = 301;
+        let mut force = false;
+        let mut rest = fields[2..].iter();
+        if let Some(field) = rest.next() {
+            let (code, forced) = match field.strip_suffix('!') {
+                Some(code) => (code, true),
+                None => (*field, false),
+            };
+            match code.parse::<u16>() {
+                Ok(code) if (200..600).contains(&code) => {
+                    status = code;
+                    force = forced;
+                }
+                _ => {
+                    problems.push(error(line, &format!("`{}` is not an HTTP status", field)));
+                    continue;
+                }
+            }
+        }
+        if rest.next().is_some() {
+            problems.push(warning(
+                line,
+                "conditions (Country=, Language=, Role=...) are only evaluated by the host",
+            ));
+        }
+        if !(300..400).contains(&status) && !to.starts_with('/') {
+            problems.push(warning(
+                line,
+                "rewrites to another site are only applied by the host, not `shtml dev`",
+            ));
+        }
+
+        rules.push(Redirect {
+            from: from.to_string(),
+            to: to.to_string(),
+            status,
+            force,
+        });
+    }
+
+    (rules, problems)
+}
+
+pub fn parse_headers(text: &str) -> (Vec<HeaderRule>, Vec<Problem>) {
+    let mut rules: Vec<HeaderRule> = Vec::new();
+    let mut problems = Vec::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if !raw.starts_with([' ', '\t']) {
+            if !trimmed.starts_with('/') {
+                problems.push(error(line, &format!("`{}` must be a path starting with '/'", trimmed)));
+                continue;
+            }
+            if let Some(problem) = check_pattern(trimmed, line) {
+                problems.push(problem);
+                continue;
+            }
+            rules.push(HeaderRule {
+                pattern: trimmed.to_string(),
+                headers: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(rule) = rules.last_mut() else {
+            problems.push(error(line, "header before any path"));
+            continue;
+        };
+        match trimmed.split_once(':') {
+            Some((name, value)) if is_token(name.trim()) => {
+                rule.headers
+                    .push((name.trim().to_string(), value.trim().to_string()));
+            }
+            _ => problems.push(error(line, &format!("`{}` is not a `Name: value` header", trimmed))),
+        }
+    }
+
+    (rules, problems)
+}
+
+// A splat is only supported as the last segment
+fn check_pattern(pattern: &str, line: usize) -> Option<Problem> {
+    let segments: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+    let misplaced = segments
+        .iter()
+        .take(segments.len().saturating_sub(1))
+        .any(|s| s.contains('*'));
+    let partial = segments.last().is_some_and(|s| s.contains('*') && *s != "*");
+    (misplaced || partial).then(|| {
+        error(
+            line,
+            &format!("`{}`: `*` must be a whole, final path segment", pattern),
+        )
+    })
+}
+
+/// Matches `path` against a pattern of literal segments, `:name` segments
+/// and a final `*`. Trailing slashes don't matter. Returns the captures,
+/// with the splat under `splat`.
+fn match_pattern(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
+    let pattern_segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let mut captures = HashMap::new();
+
+    for (i, segment) in pattern_segments.iter().enumerate() {
+        if *segment == "*" {
+            captures.insert("splat".to_string(), path_segments.get(i..).unwrap_or(&[]).join("/"));
+            return Some(captures);
+        }
+        let actual = path_segments.get(i)?;
+        match segment.strip_prefix(':') {
+            Some(name) => {
+                captures.insert(name.to_string(), actual.to_string());
+            }
+            // Patterns may be written percent-encoded; paths arrive decoded
+            None if url::percent_decode(segment).is_some_and(|s| s == *actual) => {}
+            None => return None,
+        }
+    }
+
+    (pattern_segments.len() == path_segments.len()).then_some(captures)
+}
+
+fn is_token(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+fn error(line: usize, message: &str) -> Problem {
+    Problem {
+        line,
+        message: message.to_string(),
+        is_error: true,
+    }
+}
+
+fn warning(line: usize, message: &str) -> Problem {
+    Problem {
+        line,
+        message: message.to_string(),
+        is_error: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(line: &str) -> Redirect {
+        let (mut rules, problems) = parse_redirects(line);
+        assert!(problems.is_empty(), "{:?}", problems);
+        rules.remove(0)
+    }
+
+    #[test]
+    fn parses_status_and_force() {
+        let (rules, problems) = parse_redirects(
+            "# comment\n/a /b\n/c /d 302\n/e /f 200!\n\n/g https://example.com/g 301 # old\n",
+        );
+        assert!(problems.is_empty());
+        let parsed: Vec<_> = rules.iter().map(|r| (r.from.as_str(), r.status, r.force)).collect();
+        assert_eq!(parsed, [("/a", 301, false), ("/c", 302, false), ("/e", 200, true), ("/g", 301, false)]);
+    }
+
+    #[test]
+    fn reports_invalid_lines() {
+        let (rules, problems) = parse_redirects("/a\nold /b\n/c b\n/d /e 999\n/f/*/g /h\n/i /j 200 Country=us\n");
+        assert_eq!(rules.len(), 1);
+        let lines: Vec<_> = problems.iter().map(|p| (p.line, p.is_error)).collect();
+        assert_eq!(lines, [(1, true), (2, true), (3, true), (4, true), (5, true), (6, false)]);
+    }
+
+    #[test]
+    fn matches_placeholders_and_splat() {
+        let captures = match_pattern("/users/:id/*", "/users/42/a/b/").unwrap();
+        assert_eq!(captures["id"], "42");
+        assert_eq!(captures["splat"], "a/b");
+        assert_eq!(match_pattern("/blog/*", "/blog").unwrap()["splat"], "");
+        assert!(match_pattern("/users/:id", "/users/42/more").is_none());
+        assert!(match_pattern("/users/:id", "/posts/42").is_none());
+        // Patterns may be written encoded, paths arrive decoded
+        assert!(match_pattern("/caf%C3%A9", "/café").is_some());
+    }
+
+    #[test]
+    fn fills_in_target() {
+        assert_eq!(rule("/old-blog/* /blog/:splat").target("/old-blog/2024/post").unwrap(), "/blog/2024/post");
+        assert_eq!(
+            rule("/users/:id /profile?id=:id&identity=x 302").target("/users/7").unwrap(),
+            "/profile?id=7&identity=x"
+        );
+        assert!(rule("/a/* /b/:splat").target("/c").is_none());
+    }
+
+    #[test]
+    fn encodes_captures_in_target() {
+        let rule = rule("/old-blog/* /blog/:splat");
+        // Decoded from %0D%0A by the request parser: must not reach a header raw
+        assert_eq!(
+            rule.target("/old-blog/\r\nSet-Cookie: x=y").unwrap(),
+            "/blog/%0D%0ASet-Cookie:%20x=y"
+        );
+        assert_eq!(rule.target("/old-blog/café").unwrap(), "/blog/caf%C3%A9");
+        // Decoded from %2525: decoding the target once gives back the path
+        let target = rule.target("/old-blog/%25").unwrap();
+        assert_eq!(target, "/blog/%2525");
+        assert_eq!(url::percent_decode(&target).unwrap(), "/blog/%25");
+    }
+
+    #[test]
+    fn parses_headers() {
+        let (rules, problems) = parse_headers("/Assets/*\n  Cache-Control: max-age=60\n  bad header\n/*\n\tX-Frame-Options: DENY\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, 3);
+        assert_eq!(rules[0].headers, [("Cache-Control".to_string(), "max-age=60".to_string())]);
+        assert!(rules[0].matches("/Assets/logo.png"));
+        assert!(!rules[0].matches("/index.html"));
+        assert!(rules[1].matches("/"));
+    }
+}
@@ -1,6 +1,11 @@
 use clap::{Parser, Subcommand};
 use std::process;
 
+mod browser;
+mod config;
+mod dev_events;
+mod host_files;
+mod network;
 mod server;
 mod commands;
 mod tui;
@@ -26,6 +31,34 @@ enum Commands {
         /// Port to run on (default: 3000)
         #[arg(short, long, default_value = "3000")]
         port: u16,
+        /// Address to listen on (default: 0.0.0.0, reachable from the local network
+        /// over IPv4; use :: to accept and list IPv6 addresses too)
+        #[arg(long, default_value = "0.0.0.0")]
+        host: String,
+        /// Fail if the port is taken instead of trying the next one
+        #[arg(long)]
+        strict_port: bool,
+        /// Serve over HTTPS with a locally trusted certificate
+        #[arg(long)]
+        https: bool,
+        /// Serve mock API responses from fixture files (default directory: mocks)
+        #[arg(long, value_name = "DIR", num_args = 0..=1, default_missing_value = "mocks")]
+        mocks: Option<String>,
+        /// Require an access token from other devices (generated unless given)
+        #[arg(long, value_name = "TOKEN", num_args = 0..=1, default_missing_value = "")]
+        token: Option<String>,
+        /// Only answer clients from this network, e.g. 192.168.1.0/24 (repeatable)
+        #[arg(long, value_name = "CIDR")]
+        allow: Vec<String>,
+        /// Open the site in a browser once the first build succeeds
+        #[arg(long)]
+        open: bool,
+        /// Log to the terminal instead of the dashboard (automatic without a TTY)
+        #[arg(long)]
+        no_tui: bool,
+        /// Print newline-delimited JSON events on stdout, for editors and scripts
+        #[arg(long)]
+        json: bool,
     },
     /// Build production HTML
     Build {
@@ -40,7 +73,29 @@ fn main() {
 
     let result = match cli.command {
         Commands::Init { name } => commands::init::run(name),
-        Commands::Dev { port } => commands::dev::run(port),
+        Commands::Dev {
+            port,
+            host,
+            strict_port,
+            https,
+            mocks,
+            token,
+            allow,
+            open,
+            no_tui,
+            json,
+        } => commands::dev::run(commands::dev::Options {
+            port,
+            host,
+            strict_port,
+            https,
+            mocks,
+            token,
+            allow,
+            open,
+            no_tui,
+            json,
+        }),
         Commands::Build { output } => commands::build::run(&output),
     };
 
@@ -0,0 +1,79 @@
+// Addresses other devices can reach the dev server at.
+
+use std::net::IpAddr;
+
+// Interfaces that are rarely the network a phone is on
+const VIRTUAL_PREFIXES: &[&str] = &[
+    "docker", "br-", "veth", "virbr", "vmnet", "vboxnet", "utun", "tun", "tap", "wg", "zt",
+    "tailscale",
+];
+
+#[derive(Debug, Clone)]
+pub struct Address {
+    /// Interface name, or `mDNS` for the hostname
+    pub interface: String,
+    /// An IP address or a hostname, as it goes into a certificate
+    pub name: String,
+}
+
+impl Address {
+    pub fn new(interface: &str, name: impl Into<String>) -> Self {
+        Address {
+            interface: interface.to_string(),
+            name: name.into(),
+        }
+    }
+
+    /// The name as written in a URL, with IPv6 addresses in brackets.
+    pub fn url_host(&self) -> String {
+        if self.name.contains(':') {
+            format!("[{}]", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// Non-loopback addresses, IPv6 ones only if `ipv6` (the listener accepts
+/// them). Link-local IPv6 is left out: browsers can't open it.
+pub fn lan_addresses(ipv6: bool) -> Vec<Address> {
+    let Ok(interfaces) = if_addrs::get_if_addrs() else {
+        return Vec::new();
+    };
+    let mut found: Vec<(bool, bool, Address)> = interfaces
+        .into_iter()
+        .filter(|iface| !iface.is_loopback())
+        .filter(|iface| match iface.ip() {
+            IpAddr::V4(_) => true,
+            IpAddr::V6(ip) => ipv6 && !ip.is_unicast_link_local(),
+        })
+        .map(|iface| {
+            let is_virtual = VIRTUAL_PREFIXES.iter().any(|p| iface.name.starts_with(p));
+            let address = Address::new(&iface.name, iface.ip().to_string());
+            (is_virtual, iface.ip().is_ipv6(), address)
+        })
+        .collect();
+    // Stable: within a group, the order the system lists interfaces in
+    found.sort_by_key(|(is_virtual, is_ipv6, _)| (*is_virtual, *is_ipv6));
+    found.into_iter().map(|(_, _, address)| address).collect()
+}
+
+/// This machine's `.local` name, as mDNS responders (Bonjour, Avahi)
+/// announce it.
+pub fn mdns_hostname() -> Option<String> {
+    let hostname = gethostname::gethostname().into_string().ok()?;
+    // A configured domain (`host.example.com`) isn't part of the mDNS name
+    let label = dns_label(hostname.split('.').next()?)?;
+    (label != "localhost").then(|| format!("{}.local", label))
+}
+
+/// `name` as a DNS label: lowercase letters, digits and dashes.
+pub fn dns_label(name: &str) -> Option<String> {
+    let label: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let label = label.trim_matches('-');
+    let label = &label[..label.len().min(63)];
+    (!label.is_empty()).then(|| label.to_string())
+}
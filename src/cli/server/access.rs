@@ -0,0 +1,208 @@
+// Who may use the dev server: the `--token` cookie and the `--allow` list.
+
+use super::http::{Request, Response};
+use super::url;
+use std::{net::IpAddr, str::FromStr, sync::OnceLock};
+
+const COOKIE: &str = "shtml_token";
+const QUERY_PARAM: &str = "token";
+
+static ACCESS: OnceLock<Access> = OnceLock::new();
+
+struct Access {
+    token: Option<String>,
+    allow: Vec<Cidr>,
+}
+
+/// A network such as `192.168.1.0/24` or `fd00::/8`; a bare address is a
+/// network of one.
+#[derive(Debug, Clone)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u32,
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("`{}` is not an address or a network like 192.168.1.0/24", s);
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix: u32 = match prefix {
+            Some(prefix) => prefix.parse().ok().filter(|p| *p <= max).ok_or_else(invalid)?,
+            None => max,
+        };
+        // Peers are compared canonicalised, so `::ffff:a.b.c.d` networks must be too
+        if let IpAddr::V6(v6) = addr {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                let prefix = prefix.checked_sub(96).ok_or_else(invalid)?;
+                return Ok(Cidr { addr: IpAddr::V4(v4), prefix });
+            }
+        }
+        Ok(Cidr { addr, prefix })
+    }
+}
+
+impl Cidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Turns the checks on for the rest of the process.
+pub fn enable(token: Option<String>, allow: Vec<Cidr>) {
+    let _ = ACCESS.set(Access { token, allow });
+}
+
+/// A new random token, hex encoded.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    rustls::crypto::ring::default_provider()
+        .secure_random
+        .fill(&mut bytes)
+        .expect("system random number generator failed");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The response to send instead of serving `req` from `peer`, if it may not
+/// be served.
+pub fn check(req: &Request, peer: IpAddr) -> Option<Response> {
+    let access = ACCESS.get()?;
+    if peer.to_canonical().is_loopback() {
+        return None;
+    }
+
+    if !access.allow.is_empty() && !access.allow.iter().any(|net| net.contains(peer)) {
+        return Some(Response::new(403).text("Forbidden"));
+    }
+
+    // The CA certificate is public, and a phone has to fetch it before it
+    // can open the token URL over HTTPS
+    if req.path == super::internal::CA_CERT {
+        return None;
+    }
+
+    let token = access.token.as_deref()?;
+    if cookie(req, COOKIE).is_some_and(|value| same(value, token)) {
+        return None;
+    }
+    if query_token(req).is_some_and(|value| same(&value, token)) {
+        // Keep the token out of the address bar and history
+        let mut resp = Response::new(302).text("Found");
+        resp.headers.insert("Location".into(), without_token(req));
+        resp.headers.insert(
+            "Set-Cookie".into(),
+            format!("{}={}; Path=/; HttpOnly; SameSite=Lax", COOKIE, token),
+        );
+        return Some(resp);
+    }
+
+    let mut resp = Response::new(401).html(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"UTF-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\
+         <title>Access token required</title></head>\
+         <body style=\"font-family: system-ui; padding: 40px 20px;\">\
+         <h1>🔒 Access token required</h1>\
+         <p>This dev server needs its access token. Scan the QR code in the terminal \
+         running <code>shtml dev</code>, or open the URL it shows.</p></body></html>\n",
+    );
+    resp.headers.insert("Cache-Control".into(), "no-store".into());
+    Some(resp)
+}
+
+fn cookie<'a>(req: &'a Request, name: &str) -> Option<&'a str> {
+    req.header("cookie")?.split(';').find_map(|pair| {
+        let (key, value) = pair.trim().split_once('=')?;
+        (key == name).then_some(value)
+    })
+}
+
+fn query_token(req: &Request) -> Option<String> {
+    req.query.as_deref()?.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == QUERY_PARAM).then(|| url::percent_decode(value)).flatten()
+    })
+}
+
+// The request URI with the token parameter dropped
+fn without_token(req: &Request) -> String {
+    let query: Vec<&str> = req
+        .query
+        .as_deref()
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty() && pair.split('=').next() != Some(QUERY_PARAM))
+        .collect();
+    let path = url::encode_path(&req.path);
+    if query.is_empty() {
+        path
+    } else {
+        format!("{}?{}", path, query.join("&"))
+    }
+}
+
+// Compares in constant time, so the token can't be guessed byte by byte
+fn same(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allows(cidr: &str, ip: &str) -> bool {
+        cidr.parse::<Cidr>().unwrap().contains(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn matches_networks() {
+        assert!(allows("192.168.1.0/24", "192.168.1.200"));
+        assert!(!allows("192.168.1.0/24", "192.168.2.1"));
+        assert!(allows("0.0.0.0/0", "203.0.113.9"));
+        assert!(!allows("0.0.0.0/0", "fd00::1"));
+        assert!(allows("::/0", "fd00::1"));
+        assert!(allows("fd00::/8", "fd12::34"));
+        assert!(!allows("fd00::/8", "fe80::1"));
+    }
+
+    #[test]
+    fn matches_single_addresses() {
+        assert!(allows("10.0.0.5", "10.0.0.5"));
+        assert!(allows("10.0.0.5/32", "10.0.0.5"));
+        assert!(!allows("10.0.0.5/32", "10.0.0.6"));
+        assert!(allows("fd00::2/128", "fd00::2"));
+        assert!(!allows("fd00::2", "fd00::3"));
+    }
+
+    #[test]
+    fn matches_ipv4_mapped_peers() {
+        // A dual-stack listener sees IPv4 clients as ::ffff:a.b.c.d
+        assert!(allows("192.168.1.0/24", "::ffff:192.168.1.7"));
+        assert!(!allows("192.168.1.0/24", "::ffff:192.168.2.7"));
+        assert!(allows("::ffff:192.168.1.0/120", "192.168.1.7"));
+        assert!(allows("::ffff:192.168.1.0/120", "::ffff:192.168.1.7"));
+        assert!(!allows("::ffff:192.168.1.0/120", "192.168.2.7"));
+    }
+
+    #[test]
+    fn rejects_invalid_networks() {
+        for cidr in ["192.168.1.0/33", "fd00::/129", "192.168.1.0/-1", "192.168.1.0/", "host/24", "::ffff:10.0.0.0/64"] {
+            assert!(cidr.parse::<Cidr>().is_err(), "{}", cidr);
+        }
+    }
+}
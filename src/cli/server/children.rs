@@ -0,0 +1,106 @@
+// Swift processes started by the dev server, killed on quit.
+
+use std::{
+    io::{self, Read},
+    process::{Child, Command, Output, Stdio},
+    sync::Mutex,
+    thread,
+    time::Duration,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+struct Running {
+    children: Vec<Child>,
+    // Set on shutdown; nothing new is started afterwards
+    stopped: bool,
+}
+
+static RUNNING: Mutex<Running> = Mutex::new(Running {
+    children: Vec::new(),
+    stopped: false,
+});
+
+/// Like `Command::output`, but killed by `kill_all`.
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    let mut running = RUNNING.lock().unwrap();
+    if running.stopped {
+        return Err(io::Error::new(io::ErrorKind::Interrupted, "dev server is shutting down"));
+    }
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(cmd, 0);
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Drained as the process runs, or a chatty build fills the pipe and stalls
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let id = child.id();
+    running.children.push(child);
+    drop(running);
+
+    let status = loop {
+        let mut running = RUNNING.lock().unwrap();
+        let child = running
+            .children
+            .iter_mut()
+            .find(|child| child.id() == id)
+            .expect("only output() removes its child");
+        match child.try_wait() {
+            Ok(None) => {}
+            // Already reaped by `try_wait`
+            Ok(Some(status)) => {
+                running.children.retain(|child| child.id() != id);
+                break status;
+            }
+            Err(e) => {
+                running.children.retain(|child| child.id() != id);
+                return Err(e);
+            }
+        }
+        drop(running);
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Kills every tracked process and refuses to start new ones.
+pub fn kill_all() {
+    let mut running = RUNNING.lock().unwrap();
+    running.stopped = true;
+    for child in &mut running.children {
+        kill_group(child);
+    }
+}
+
+#[cfg(unix)]
+fn kill_group(child: &mut Child) {
+    // The group's id is the child's pid; a negative pid signals the group.
+    // Until the child is reaped its pid can't be reused, so this can't hit
+    // an unrelated group.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
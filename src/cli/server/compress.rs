@@ -0,0 +1,170 @@
+// Brotli/gzip compression of text responses.
+
+use super::http::{Body, Request, Response};
+use flate2::{read::GzEncoder, Compression};
+use std::io::{self, Read};
+
+// Smaller bodies aren't worth the CPU or the extra headers
+const MIN_SIZE: u64 = 1024;
+// Fast settings: this runs on every request
+const BROTLI_QUALITY: u32 = 5;
+const BROTLI_WINDOW: u32 = 22;
+const BUFFER_SIZE: usize = 16 * 1024;
+
+#[derive(Clone, Copy)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    fn encode(self, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        match self {
+            Encoding::Brotli => Box::new(brotli::CompressorReader::new(
+                reader,
+                BUFFER_SIZE,
+                BROTLI_QUALITY,
+                BROTLI_WINDOW,
+            )),
+            Encoding::Gzip => Box::new(GzEncoder::new(reader, Compression::default())),
+        }
+    }
+}
+
+/// Compresses `resp` in place if both the client and the content allow it.
+pub fn compress_response(req: &Request, resp: &mut Response) {
+    let compressible = resp
+        .headers
+        .get("Content-Type")
+        .is_some_and(|t| is_compressible(t));
+    if !compressible {
+        return;
+    }
+
+    // The representation depends on Accept-Encoding from here on, even when
+    // this particular response goes out uncompressed
+    add_vary(resp);
+
+    // Partial content refers to the identity bytes; 304 and friends have no body
+    if resp.status != 200 || resp.headers.contains_key("Content-Encoding") {
+        return;
+    }
+    let len = match &resp.body {
+        Body::Bytes(bytes) => bytes.len() as u64,
+        Body::Stream { len: Some(len), .. } => *len,
+        // Live streams (proxied chunked bodies) must not sit in the encoder
+        Body::Stream { len: None, .. } => return,
+    };
+    if len < MIN_SIZE {
+        return;
+    }
+    let Some(encoding) = req.header("accept-encoding").and_then(negotiate) else {
+        return;
+    };
+
+    let body = std::mem::replace(&mut resp.body, Body::Bytes(Vec::new()));
+    resp.body = match body {
+        Body::Bytes(bytes) => {
+            // Keep in-memory bodies in memory so they still get a Content-Length
+            let mut compressed = Vec::new();
+            match encoding
+                .encode(Box::new(io::Cursor::new(bytes.clone())))
+                .read_to_end(&mut compressed)
+            {
+                Ok(_) => Body::Bytes(compressed),
+                Err(_) => {
+                    resp.body = Body::Bytes(bytes);
+                    return;
+                }
+            }
+        }
+        Body::Stream { reader, .. } => Body::Stream {
+            reader: encoding.encode(reader),
+            len: None,
+        },
+    };
+
+    resp.headers
+        .insert("Content-Encoding".into(), encoding.name().into());
+    resp.headers.remove("Accept-Ranges");
+    // Byte-for-byte different from the identity file, so the validator can
+    // no longer be strong (If-None-Match compares weakly and still matches)
+    if let Some(etag) = resp.headers.get_mut("ETag") {
+        if !etag.starts_with("W/") {
+            *etag = format!("W/{}", etag);
+        }
+    }
+}
+
+fn is_compressible(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    (mime.starts_with("text/") && mime != "text/event-stream")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime.as_str(),
+            "application/javascript" | "application/json" | "application/xml"
+        )
+}
+
+fn add_vary(resp: &mut Response) {
+    match resp.headers.get_mut("Vary") {
+        Some(vary) => {
+            let present = vary
+                .split(',')
+                .any(|v| v.trim().eq_ignore_ascii_case("accept-encoding"));
+            if !present {
+                vary.push_str(", Accept-Encoding");
+            }
+        }
+        None => {
+            resp.headers
+                .insert("Vary".into(), "Accept-Encoding".into());
+        }
+    }
+}
+
+// Picks the best supported coding from an Accept-Encoding header, honouring
+// q-values (`gzip;q=0` means "never gzip")
+fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+    let mut brotli = None;
+    let mut gzip = None;
+    let mut wildcard = None;
+
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';');
+        let coding = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let q = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        match coding.as_str() {
+            "br" => brotli = Some(q),
+            "gzip" | "x-gzip" => gzip = Some(q),
+            "*" => wildcard = Some(q),
+            _ => {}
+        }
+    }
+
+    let brotli = brotli.or(wildcard).unwrap_or(0.0);
+    let gzip = gzip.or(wildcard).unwrap_or(0.0);
+    if brotli > 0.0 && brotli >= gzip {
+        Some(Encoding::Brotli)
+    } else if gzip > 0.0 {
+        Some(Encoding::Gzip)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,119 @@
+// Live-reload event streams (`/__shtml/events`).
+
+use super::tls::Connection;
+use crate::dev_events::{self, DevEvent};
+use rustls::{ServerConnection, StreamOwned};
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+use tokio::{
+    io::AsyncWriteExt,
+    net::TcpStream,
+    sync::{broadcast, watch},
+    time,
+};
+
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+// Events buffered for a slow client before it starts skipping them
+const CAPACITY: usize = 16;
+
+const HEADERS: &str = "HTTP/1.1 200 OK\r\n\
+                       Content-Type: text/event-stream\r\n\
+                       Cache-Control: no-cache\r\n\
+                       Connection: keep-alive\r\n\
+                       X-Accel-Buffering: no\r\n\
+                       \r\n";
+
+lazy_static::lazy_static! {
+    static ref EVENTS: broadcast::Sender<String> = broadcast::channel(CAPACITY).0;
+}
+
+/// Sends `msg` to every open page.
+pub fn broadcast(msg: &str) {
+    // Only fails when no page is listening
+    let _ = EVENTS.send(msg.to_string());
+}
+
+pub fn client_count() -> usize {
+    EVENTS.receiver_count()
+}
+
+// The socket of a stream, taken off the blocking handler
+enum Stream {
+    Plain(TcpStream),
+    // rustls only encrypts; the records it produces are written here
+    Tls(Box<ServerConnection>, TcpStream),
+}
+
+impl Stream {
+    fn new(conn: Connection) -> io::Result<Self> {
+        match conn {
+            Connection::Plain(stream) => Ok(Stream::Plain(into_async(stream)?)),
+            Connection::Tls(tls) => {
+                let StreamOwned { conn, sock } = *tls;
+                Ok(Stream::Tls(Box::new(conn), into_async(sock)?))
+            }
+        }
+    }
+
+    async fn send(&mut self, data: &[u8]) -> io::Result<()> {
+        let write = async {
+            match self {
+                Stream::Plain(stream) => stream.write_all(data).await,
+                Stream::Tls(conn, sock) => {
+                    conn.writer().write_all(data)?;
+                    let mut records = Vec::new();
+                    while conn.wants_write() {
+                        conn.write_tls(&mut records)?;
+                    }
+                    sock.write_all(&records).await
+                }
+            }
+        };
+        time::timeout(WRITE_TIMEOUT, write)
+            .await
+            .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+    }
+}
+
+fn into_async(stream: std::net::TcpStream) -> io::Result<TcpStream> {
+    stream.set_nonblocking(true)?;
+    TcpStream::from_std(stream)
+}
+
+/// Streams events to `conn` until the page goes away or the server shuts
+/// down, which the page is told about with a last `server-shutdown` event.
+/// A page going away is the normal end of a stream, not an error: every
+/// reload closes one.
+pub async fn stream(conn: Connection, mut shutdown: watch::Receiver<bool>) -> io::Result<()> {
+    let address = conn.tcp().peer_addr()?.ip().to_canonical().to_string();
+    let mut events = EVENTS.subscribe();
+    let mut stream = Stream::new(conn)?;
+    if stream.send(HEADERS.as_bytes()).await.is_err()
+        || stream.send(b"data: connected\n\n").await.is_err()
+    {
+        return Ok(());
+    }
+    dev_events::emit(DevEvent::ClientConnected {
+        address,
+        clients: client_count(),
+    });
+
+    loop {
+        let (data, last) = tokio::select! {
+            event = events.recv() => match event {
+                Ok(msg) => (format!("data: {}\n\n", msg), false),
+                // Missed events are all reloads of some kind; the next one will do
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = time::sleep(KEEPALIVE_INTERVAL) => (": keepalive\n\n".to_string(), false),
+            _ = shutdown.wait_for(|stopped| *stopped) => ("data: server-shutdown\n\n".to_string(), true),
+        };
+        if stream.send(data.as_bytes()).await.is_err() || last {
+            return Ok(());
+        }
+    }
+}
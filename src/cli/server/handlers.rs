@@ -0,0 +1,33 @@
+// Hook for custom dev-server endpoints.
+
+use super::http::{Request, Response};
+use std::sync::RwLock;
+
+pub trait Handler: Send + Sync {
+    fn handle(&self, req: &Request) -> Option<Response>;
+}
+
+impl<F> Handler for F
+where
+    F: Fn(&Request) -> Option<Response> + Send + Sync,
+{
+    fn handle(&self, req: &Request) -> Option<Response> {
+        self(req)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref HANDLERS: RwLock<Vec<Box<dyn Handler>>> = RwLock::new(Vec::new());
+}
+
+/// Adds a handler; handlers are tried in registration order.
+pub fn register(handler: impl Handler + 'static) {
+    if let Ok(mut handlers) = HANDLERS.write() {
+        handlers.push(Box::new(handler));
+    }
+}
+
+pub fn dispatch(req: &Request) -> Option<Response> {
+    let handlers = HANDLERS.read().ok()?;
+    handlers.iter().find_map(|handler| handler.handle(req))
+}
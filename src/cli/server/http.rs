@@ -0,0 +1,451 @@
+// HTTP/1.x message reading and writing.
+
+use super::{compress, url};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Read, Write},
+    time::Duration,
+};
+
+/// How long an idle persistent connection is kept open between requests
+pub const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Requests served on one connection before it is closed
+pub const MAX_REQUESTS_PER_CONNECTION: usize = 100;
+
+/// Largest request body read into memory; bigger ones get a 413
+pub const MAX_BODY_BYTES: u64 = 16 * 1024 * 1024;
+
+const MAX_HEAD_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub version: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+    /// Filled in by `read_body`; proxied requests stream theirs instead
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    pub fn is_http11(&self) -> bool {
+        self.version != "HTTP/1.0"
+    }
+
+    pub fn is_chunked(&self) -> bool {
+        self.header("transfer-encoding")
+            .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"))
+    }
+
+    pub fn content_length(&self) -> Option<u64> {
+        self.header("content-length").and_then(|len| len.parse().ok())
+    }
+
+    /// Whether the client waits for `100 Continue` before sending its body
+    pub fn expects_continue(&self) -> bool {
+        self.header("expect")
+            .is_some_and(|e| e.eq_ignore_ascii_case("100-continue"))
+    }
+
+    pub fn wants_keep_alive(&self) -> bool {
+        let connection = self.header("connection").unwrap_or("").to_ascii_lowercase();
+        let mut tokens = connection.split(',').map(str::trim);
+        if self.is_http11() {
+            !tokens.any(|t| t == "close")
+        } else {
+            tokens.any(|t| t == "keep-alive")
+        }
+    }
+}
+
+pub enum Body {
+    Bytes(Vec<u8>),
+    /// Streamed from a reader; sent chunked when `len` is unknown
+    Stream {
+        reader: Box<dyn Read + Send>,
+        len: Option<u64>,
+    },
+}
+
+pub struct Response {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    /// Headers that may appear more than once (`Set-Cookie`), written after
+    /// `headers`
+    pub extra_headers: Vec<(String, String)>,
+    pub body: Body,
+}
+
+impl Response {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: HashMap::new(),
+            extra_headers: Vec::new(),
+            body: Body::Bytes(Vec::new()),
+        }
+    }
+
+    pub fn html(mut self, content: &str) -> Self {
+        self.body = Body::Bytes(content.as_bytes().to_vec());
+        self.headers
+            .insert("Content-Type".into(), "text/html; charset=utf-8".into());
+        self
+    }
+
+    pub fn js(mut self, content: &str) -> Self {
+        self.body = Body::Bytes(content.as_bytes().to_vec());
+        self.headers
+            .insert("Content-Type".into(), "application/javascript".into());
+        self
+    }
+
+    pub fn text(mut self, content: &str) -> Self {
+        self.body = Body::Bytes(content.as_bytes().to_vec());
+        self.headers
+            .insert("Content-Type".into(), "text/plain".into());
+        self
+    }
+}
+
+pub fn read_request<R: BufRead>(reader: &mut R) -> io::Result<Option<Request>> {
+    let mut head = reader.by_ref().take(MAX_HEAD_BYTES);
+
+    // Tolerate stray blank lines between pipelined requests
+    let mut request_line = String::new();
+    loop {
+        request_line.clear();
+        let n = head.read_line(&mut request_line)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        if !request_line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let request_line = request_line.trim_end();
+    let mut parts = request_line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no method"))?;
+    let target = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no path"))?;
+    let target = url::parse_target(target)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "bad request target"))?;
+    let version = parts.next().unwrap_or("HTTP/1.0");
+    if !version.starts_with("HTTP/1.") {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "unsupported HTTP version"));
+    }
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        let n = head.read_line(&mut line)?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "eof"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = line.split_once(':') {
+            headers.insert(k.trim().to_ascii_lowercase(), v.trim().to_string());
+        }
+    }
+
+    Ok(Some(Request {
+        method: method.to_string(),
+        path: target.path,
+        query: target.query,
+        version: version.to_string(),
+        headers,
+        body: Vec::new(),
+    }))
+}
+
+pub enum BodyError {
+    TooLarge,
+    Malformed,
+    Io(io::Error),
+}
+
+impl From<io::Error> for BodyError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::InvalidData => BodyError::Malformed,
+            _ => BodyError::Io(e),
+        }
+    }
+}
+
+/// Reads the request body (Content-Length or chunked) into `req.body`, up to
+/// `MAX_BODY_BYTES`. After an error the connection can't be reused.
+pub fn read_body<R: BufRead>(reader: &mut R, req: &mut Request) -> Result<(), BodyError> {
+    let mut body = Vec::new();
+    if req.is_chunked() {
+        // One byte over the limit is enough to know it's too large
+        ChunkedReader::new(reader.by_ref())
+            .take(MAX_BODY_BYTES + 1)
+            .read_to_end(&mut body)?;
+        if body.len() as u64 > MAX_BODY_BYTES {
+            return Err(BodyError::TooLarge);
+        }
+    } else if req.header("content-length").is_some() {
+        let len = req.content_length().ok_or(BodyError::Malformed)?;
+        if len > MAX_BODY_BYTES {
+            return Err(BodyError::TooLarge);
+        }
+        reader.by_ref().take(len).read_to_end(&mut body)?;
+        if body.len() as u64 != len {
+            return Err(BodyError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "request body shorter than Content-Length",
+            )));
+        }
+    }
+    req.body = body;
+    Ok(())
+}
+
+/// Decodes a `Transfer-Encoding: chunked` body, ending after the last chunk
+/// (trailers are read and dropped).
+pub struct ChunkedReader<R> {
+    inner: R,
+    remaining: u64,
+    started: bool,
+    done: bool,
+}
+
+impl<R: BufRead> ChunkedReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            remaining: 0,
+            started: false,
+            done: false,
+        }
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        let n = self.inner.by_ref().take(MAX_HEAD_BYTES).read_line(&mut line)?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "chunked body ended early"));
+        }
+        Ok(line)
+    }
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            // The CRLF that ends the previous chunk's data
+            if self.started {
+                self.read_line()?;
+            }
+            self.started = true;
+
+            let line = self.read_line()?;
+            let size = line.split(';').next().unwrap_or("").trim();
+            self.remaining = u64::from_str_radix(size, 16)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad chunk size"))?;
+            if self.remaining == 0 {
+                while !self.read_line()?.trim_end().is_empty() {}
+                self.done = true;
+                return Ok(0);
+            }
+        }
+
+        let max = buf.len().min(self.remaining.min(usize::MAX as u64) as usize);
+        let n = self.inner.read(&mut buf[..max])?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "chunked body ended early"));
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+pub fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+/// Writes `resp` and reports whether the connection may be reused.
+///
+/// The caller decides on persistence through the `Connection` header; it is
+/// downgraded to `close` when the body can only be delimited by closing.
+pub fn write_response<W: Write>(
+    stream: &mut W,
+    mut resp: Response,
+    req: Option<&Request>,
+) -> io::Result<bool> {
+    if let Some(req) = req {
+        compress::compress_response(req, &mut resp);
+    }
+    let head_only = req.is_some_and(|r| r.method == "HEAD");
+    let has_body = !matches!(resp.status, 100..=199 | 204 | 304);
+    let mut keep_alive = resp
+        .headers
+        .get("Connection")
+        .is_some_and(|v| !v.eq_ignore_ascii_case("close"));
+
+    let chunked = match &resp.body {
+        Body::Bytes(bytes) => {
+            if has_body {
+                resp.headers
+                    .insert("Content-Length".into(), bytes.len().to_string());
+            }
+            false
+        }
+        Body::Stream { len: Some(len), .. } => {
+            if has_body {
+                resp.headers.insert("Content-Length".into(), len.to_string());
+            }
+            false
+        }
+        Body::Stream { len: None, .. } => {
+            if req.is_none_or(Request::is_http11) {
+                resp.headers
+                    .insert("Transfer-Encoding".into(), "chunked".into());
+                true
+            } else {
+                // HTTP/1.0 has no chunked encoding: the end of the body is
+                // the end of the connection
+                keep_alive = false;
+                false
+            }
+        }
+    };
+    if !keep_alive {
+        resp.headers.insert("Connection".into(), "close".into());
+        resp.headers.remove("Keep-Alive");
+    }
+
+    let mut head = format!("HTTP/1.1 {} {}\r\n", resp.status, reason_phrase(resp.status));
+    for (k, v) in resp.headers.iter().chain(resp.extra_headers.iter().map(|(k, v)| (k, v))) {
+        head.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    head.push_str("\r\n");
+
+    let send_body = has_body && !head_only;
+    match resp.body {
+        Body::Bytes(bytes) => {
+            // One write for head and body, so small responses go out in a
+            // single packet
+            let mut out = head.into_bytes();
+            if send_body {
+                out.extend_from_slice(&bytes);
+            }
+            stream.write_all(&out)?;
+        }
+        Body::Stream { mut reader, len } => {
+            stream.write_all(head.as_bytes())?;
+            if send_body {
+                if chunked {
+                    write_chunked(stream, &mut reader)?;
+                } else {
+                    let copied = io::copy(&mut reader, stream)?;
+                    if len.is_some_and(|len| copied != len) {
+                        // The framing is broken; the client must not reuse it
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "body shorter than Content-Length",
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    stream.flush()?;
+    Ok(keep_alive)
+}
+
+/// "content-type" -> "Content-Type", the spelling used for response headers
+pub fn canonical_name(name: &str) -> String {
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => {
+                    first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+                }
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+pub fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        410 => "Gone",
+        411 => "Length Required",
+        412 => "Precondition Failed",
+        413 => "Content Too Large",
+        416 => "Range Not Satisfiable",
+        417 => "Expectation Failed",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        505 => "HTTP Version Not Supported",
+        _ => match status / 100 {
+            1 => "Informational",
+            2 => "Success",
+            3 => "Redirection",
+            4 => "Client Error",
+            _ => "Server Error",
+        },
+    }
+}
+
+pub fn write_chunked<W: Write, R: Read>(stream: &mut W, reader: &mut R) -> io::Result<()> {
+    let mut buf = vec![0u8; 16 * 1024];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        write!(stream, "{:X}\r\n", n)?;
+        stream.write_all(&buf[..n])?;
+        stream.write_all(b"\r\n")?;
+        // Streamed bodies are often live (proxied events), don't sit on them
+        stream.flush()?;
+    }
+    stream.write_all(b"0\r\n\r\n")
+}
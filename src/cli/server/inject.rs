@@ -0,0 +1,337 @@
+// Live-reload client injection, placed with a small HTML tokenizer.
+
+use super::internal::CLIENT_SCRIPT;
+
+// Elements whose content is never parsed as markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "script", "style", "textarea", "title", "xmp", "iframe", "noembed", "noframes",
+];
+
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub name: String,
+    pub closing: bool,
+    pub attrs: Vec<(String, String)>,
+    pub start: usize,
+}
+
+impl Tag {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Iterator over the real start and end tags of an HTML document.
+pub struct Tags<'a> {
+    html: &'a str,
+    pos: usize,
+    raw_text: Option<String>,
+}
+
+impl<'a> Tags<'a> {
+    pub fn new(html: &'a str) -> Self {
+        Self {
+            html,
+            pos: 0,
+            raw_text: None,
+        }
+    }
+}
+
+impl Iterator for Tags<'_> {
+    type Item = Tag;
+
+    fn next(&mut self) -> Option<Tag> {
+        let html = self.html;
+        let bytes = html.as_bytes();
+        let len = bytes.len();
+
+        loop {
+            // Inside <script>/<style>/...: jump straight to the matching end tag
+            if let Some(name) = self.raw_text.take() {
+                self.pos = find_raw_text_end(html, self.pos, &name).unwrap_or(len);
+            }
+
+            let lt = self.pos + html.get(self.pos..)?.find('<')?;
+            let rest = &html[lt..];
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                self.pos = comment.find("-->").map_or(len, |i| lt + 4 + i + 3);
+                continue;
+            }
+            // Bytes: the text after `<!` may not break at a char boundary
+            if rest.as_bytes().get(..9).is_some_and(|b| b.eq_ignore_ascii_case(b"<![CDATA[")) {
+                self.pos = rest.find("]]>").map_or(len, |i| lt + i + 3);
+                continue;
+            }
+            if rest.starts_with("<!") || rest.starts_with("<?") {
+                // Doctype or processing instruction
+                self.pos = rest.find('>').map_or(len, |i| lt + i + 1);
+                continue;
+            }
+
+            let closing = rest.starts_with("</");
+            let name_start = lt + if closing { 2 } else { 1 };
+            if !bytes.get(name_start).is_some_and(u8::is_ascii_alphabetic) {
+                // A stray '<' in text
+                self.pos = lt + 1;
+                continue;
+            }
+
+            let mut i = name_start;
+            while i < len && !is_space(bytes[i]) && bytes[i] != b'/' && bytes[i] != b'>' {
+                i += 1;
+            }
+            let name = html[name_start..i].to_ascii_lowercase();
+
+            let mut attrs = Vec::new();
+            loop {
+                while i < len && (is_space(bytes[i]) || bytes[i] == b'/') {
+                    i += 1;
+                }
+                if i >= len {
+                    // Unterminated tag: the rest of the document is not markup
+                    self.pos = len;
+                    return None;
+                }
+                if bytes[i] == b'>' {
+                    i += 1;
+                    break;
+                }
+
+                let attr_start = i;
+                while i < len
+                    && !is_space(bytes[i])
+                    && !matches!(bytes[i], b'=' | b'>' | b'/')
+                {
+                    i += 1;
+                }
+                let attr_name = html[attr_start..i].to_ascii_lowercase();
+
+                let mut j = i;
+                while j < len && is_space(bytes[j]) {
+                    j += 1;
+                }
+                let mut value = String::new();
+                if j < len && bytes[j] == b'=' {
+                    i = j + 1;
+                    while i < len && is_space(bytes[i]) {
+                        i += 1;
+                    }
+                    if i < len && (bytes[i] == b'"' || bytes[i] == b'\'') {
+                        let quote = bytes[i] as char;
+                        let value_start = i + 1;
+                        let value_end = html[value_start..]
+                            .find(quote)
+                            .map_or(len, |k| value_start + k);
+                        value = html[value_start..value_end].to_string();
+                        i = (value_end + 1).min(len);
+                    } else {
+                        let value_start = i;
+                        while i < len && !is_space(bytes[i]) && bytes[i] != b'>' {
+                            i += 1;
+                        }
+                        value = html[value_start..i].to_string();
+                    }
+                }
+
+                if !attr_name.is_empty() {
+                    attrs.push((attr_name, value));
+                }
+            }
+
+            if !closing && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                self.raw_text = Some(name.clone());
+            }
+            self.pos = i;
+
+            return Some(Tag {
+                name,
+                closing,
+                attrs,
+                start: lt,
+            });
+        }
+    }
+}
+
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b'\x0c')
+}
+
+// Position of the `</name` that closes a raw-text element opened before `from`
+fn find_raw_text_end(html: &str, from: usize, name: &str) -> Option<usize> {
+    let bytes = html.as_bytes();
+    let mut pos = from;
+    while let Some(i) = html[pos..].find("</") {
+        let start = pos + i;
+        let name_end = start + 2 + name.len();
+        if name_end <= bytes.len()
+            && html.is_char_boundary(name_end)
+            && html[start + 2..name_end].eq_ignore_ascii_case(name)
+            && bytes
+                .get(name_end)
+                .is_none_or(|&b| is_space(b) || b == b'/' || b == b'>')
+        {
+            return Some(start);
+        }
+        pos = start + 2;
+    }
+    None
+}
+
+/// Inserts a single live-reload `<script>` into `html`.
+///
+/// The tag goes before the real closing `</body>` (or `</head>`, `</html>`
+/// for documents without a body) and is appended to the end otherwise. If
+/// the document restricts scripts with a CSP nonce, the same nonce is put on
+/// the injected tag so the browser doesn't block it. `page` tells the client
+/// what kind of page it runs in: `app` for the generated site, or one of the
+/// dev server's own `building`, `error` and `no-output` pages.
+pub fn inject_client(html: &str, page: &str) -> String {
+    let mut body_close = None;
+    let mut head_close = None;
+    let mut html_close = None;
+    let mut csp_nonce = None;
+    let mut script_nonce = None;
+
+    for tag in Tags::new(html) {
+        match (tag.closing, tag.name.as_str()) {
+            // Parsers ignore everything but the last </body> and </html>...
+            (true, "body") => body_close = Some(tag.start),
+            (true, "html") => html_close = Some(tag.start),
+            // ...while the first </head> is the one that ends the head
+            (true, "head") => {
+                head_close.get_or_insert(tag.start);
+            }
+            (false, "meta") => {
+                let is_csp = tag
+                    .attr("http-equiv")
+                    .is_some_and(|v| v.eq_ignore_ascii_case("content-security-policy"));
+                if is_csp && csp_nonce.is_none() {
+                    csp_nonce = tag.attr("content").and_then(nonce_from_csp);
+                }
+            }
+            (false, "script") if script_nonce.is_none() => {
+                script_nonce = tag.attr("nonce").map(str::to_string);
+            }
+            _ => {}
+        }
+    }
+
+    let nonce = csp_nonce
+        .or(script_nonce)
+        .map(|nonce| format!(r#" nonce="{}""#, super::html_escape(&nonce)))
+        .unwrap_or_default();
+    let script = format!(
+        r#"<script src="{}" data-page="{}"{} defer></script>"#,
+        CLIENT_SCRIPT, page, nonce
+    );
+
+    match body_close.or(head_close).or(html_close) {
+        Some(at) => {
+            let mut out = String::with_capacity(html.len() + script.len() + 1);
+            out.push_str(&html[..at]);
+            out.push_str(&script);
+            out.push('\n');
+            out.push_str(&html[at..]);
+            out
+        }
+        None => format!("{}\n{}\n", html, script),
+    }
+}
+
+// Extracts the nonce allowed for scripts from a CSP policy string
+pub fn nonce_from_csp(policy: &str) -> Option<String> {
+    let directives: Vec<(&str, &str)> = policy
+        .split(';')
+        .filter_map(|d| {
+            let d = d.trim();
+            let (name, sources) = d.split_once(char::is_whitespace).unwrap_or((d, ""));
+            Some((name, sources)).filter(|(name, _)| !name.is_empty())
+        })
+        .collect();
+
+    // script-src-elem wins over script-src, which wins over default-src
+    ["script-src-elem", "script-src", "default-src"]
+        .iter()
+        .find_map(|wanted| {
+            directives
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(wanted))
+        })
+        .and_then(|(_, sources)| {
+            sources.split_whitespace().find_map(|source| {
+                source
+                    .strip_prefix("'nonce-")
+                    .and_then(|s| s.strip_suffix('\''))
+                    .map(str::to_string)
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(html: &str) -> Vec<String> {
+        Tags::new(html)
+            .map(|tag| format!("{}{}", if tag.closing { "/" } else { "" }, tag.name))
+            .collect()
+    }
+
+    fn script(nonce: &str) -> String {
+        format!(r#"<script src="{}" data-page="app"{} defer></script>"#, CLIENT_SCRIPT, nonce)
+    }
+
+    #[test]
+    fn skips_markup_lookalikes() {
+        let html = "<!DOCTYPE html><p title='a>b'>x</p><!-- <div> --><![CDATA[<span>]]>\
+                    <script>'</p>'</script><br/>";
+        assert_eq!(names(html), ["p", "/p", "script", "/script", "br"]);
+    }
+
+    #[test]
+    fn handles_multibyte_text_after_markup_declaration() {
+        // Like a doctype, it runs to the next '>'
+        assert_eq!(names("<p><!€€€></p>"), ["p", "/p"]);
+        assert_eq!(names("<!€"), Vec::<String>::new());
+        let html = "<body><!€€€></body>";
+        assert_eq!(inject_client(html, "app"), format!("<body><!€€€>{}\n</body>", script("")));
+    }
+
+    #[test]
+    fn injects_before_real_body_close() {
+        let html = "<html><body><script>document.write('</body>')</script>\
+                    <!-- </body> --><p>hi</p></body></html>";
+        let out = inject_client(html, "app");
+        assert_eq!(out.matches(CLIENT_SCRIPT).count(), 1);
+        assert!(out.ends_with(&format!("<p>hi</p>{}\n</body></html>", script(""))));
+    }
+
+    #[test]
+    fn falls_back_without_body() {
+        assert_eq!(
+            inject_client("<head><title>t</title></head><p>x", "app"),
+            format!("<head><title>t</title>{}\n</head><p>x", script(""))
+        );
+        assert_eq!(
+            inject_client("<html><p>x</p></html>", "app"),
+            format!("<html><p>x</p>{}\n</html>", script(""))
+        );
+        assert_eq!(inject_client("plain text", "app"), format!("plain text\n{}\n", script("")));
+    }
+
+    #[test]
+    fn copies_csp_nonce() {
+        let html = r#"<head><meta http-equiv="Content-Security-Policy" content="default-src 'self'; script-src 'nonce-abc'"></head><body></body>"#;
+        assert!(inject_client(html, "app").contains(&script(r#" nonce="abc""#)));
+        let html = r#"<body><script nonce="xyz">1</script></body>"#;
+        assert!(inject_client(html, "app").contains(&script(r#" nonce="xyz""#)));
+        assert_eq!(nonce_from_csp("script-src-elem 'nonce-a'; script-src 'nonce-b'").as_deref(), Some("a"));
+        assert_eq!(nonce_from_csp("img-src 'nonce-a'"), None);
+    }
+}
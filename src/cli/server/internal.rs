@@ -0,0 +1,234 @@
+// Dev-server internals, under a prefix no route or file of the site uses.
+
+use super::{events, get_build_status, html_escape, tls, BuildStatus, Request, Response};
+use std::{sync::OnceLock, time::Instant};
+
+pub const PREFIX: &str = "/__shtml/";
+pub const EVENTS: &str = "/__shtml/events";
+pub const STATUS: &str = "/__shtml/status";
+pub const CLIENT_SCRIPT: &str = "/__shtml/client.js";
+pub const CA_CERT: &str = "/__shtml/ca.crt";
+
+const ENDPOINTS: &[(&str, &str)] = &[
+    (PREFIX, "This page"),
+    (EVENTS, "Server-sent events stream that drives live reload"),
+    (STATUS, "Current build status: building, success, failed or unknown"),
+    (CLIENT_SCRIPT, "Live-reload client injected into served pages"),
+    (CA_CERT, "Local certificate authority to install on devices (with --https)"),
+];
+
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+pub fn mark_started() {
+    STARTED.get_or_init(Instant::now);
+}
+
+pub fn is_internal(path: &str) -> bool {
+    path.starts_with(PREFIX) || path == PREFIX.trim_end_matches('/')
+}
+
+pub fn handle(req: &Request) -> Response {
+    match req.path.as_str() {
+        "/__shtml" | PREFIX => serve_index(),
+        STATUS => {
+            let mut resp = Response::new(200).text(build_status_name());
+            resp.headers.insert("Cache-Control".into(), "no-cache".into());
+            resp
+        }
+        CLIENT_SCRIPT => serve_client_script(),
+        CA_CERT => match tls::ca_certificate() {
+            Some(pem) => {
+                let mut resp = Response::new(200);
+                resp.body = super::http::Body::Bytes(pem.as_bytes().to_vec());
+                // The type mobile browsers offer to install as a profile
+                resp.headers
+                    .insert("Content-Type".into(), "application/x-x509-ca-cert".into());
+                resp.headers.insert(
+                    "Content-Disposition".into(),
+                    "attachment; filename=\"shtml-dev-ca.crt\"".into(),
+                );
+                resp
+            }
+            None => Response::new(404).text("HTTPS is not enabled (start with `shtml dev --https`)"),
+        },
+        // Never fall through to static files or the SPA fallback
+        _ => Response::new(404).text("Unknown SHTML dev-server endpoint"),
+    }
+}
+
+fn build_status_name() -> &'static str {
+    match get_build_status() {
+        Some(BuildStatus::Building) => "building",
+        Some(BuildStatus::Success) => "success",
+        Some(BuildStatus::Failed(_)) => "failed",
+        None => "unknown",
+    }
+}
+
+fn serve_index() -> Response {
+    let rows: String = ENDPOINTS
+        .iter()
+        .map(|(path, description)| {
+            format!(
+                "<tr><td><a href=\"{0}\"><code>{0}</code></a></td><td>{1}</td></tr>\n",
+                path,
+                html_escape(description)
+            )
+        })
+        .collect();
+
+    let uptime = STARTED
+        .get()
+        .map(|started| format_uptime(started.elapsed().as_secs()))
+        .unwrap_or_else(|| "-".to_string());
+    let clients = events::client_count();
+    let cwd = std::env::current_dir()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+
+    let mut resp = Response::new(200).html(&format!(r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>SHTML Dev Server</title>
+    <style>
+        body {{ font-family: system-ui; background: #1a1a1a; color: #d4d4d4; margin: 0; padding: 40px 20px; }}
+        .container {{ max-width: 900px; margin: 0 auto; }}
+        h1 {{ color: #fff; }}
+        h2 {{ color: #667eea; margin-top: 40px; }}
+        table {{ width: 100%; border-collapse: collapse; }}
+        td {{ padding: 10px; border-bottom: 1px solid #333; vertical-align: top; }}
+        td:first-child {{ color: #888; white-space: nowrap; }}
+        a {{ color: #8fa4ff; }}
+        code {{ font-family: 'SF Mono', Monaco, Consolas, monospace; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>⚡ SHTML Dev Server</h1>
+        <h2>Server</h2>
+        <table>
+            <tr><td>Version</td><td>{}</td></tr>
+            <tr><td>Project</td><td><code>{}</code></td></tr>
+            <tr><td>Build status</td><td>{}</td></tr>
+            <tr><td>Live-reload clients</td><td>{}</td></tr>
+            <tr><td>Uptime</td><td>{}</td></tr>
+        </table>
+        <h2>Endpoints</h2>
+        <table>
+{}        </table>
+    </div>
+</body>
+</html>
+"#,
+        env!("CARGO_PKG_VERSION"),
+        html_escape(&cwd),
+        build_status_name(),
+        clients,
+        uptime,
+        rows,
+    ));
+    resp.headers.insert("Cache-Control".into(), "no-cache".into());
+    resp
+}
+
+fn format_uptime(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+fn serve_client_script() -> Response {
+    let script = r#"
+(function() {
+    if (window.__shtmlClient) return;
+    window.__shtmlClient = true;
+
+    // 'app' for the generated site, otherwise one of the dev server's own
+    // 'building', 'error' or 'no-output' pages
+    const script = document.currentScript;
+    const page = (script && script.dataset.page) || 'app';
+
+    let bar = null;
+    function createBar() {
+        bar = document.createElement('div');
+        bar.id = 'shtml-status';
+        bar.style.cssText = 'position:fixed;bottom:20px;right:20px;padding:10px 20px;border-radius:20px;font-family:system-ui;font-size:14px;font-weight:600;color:white;background:#4caf50;box-shadow:0 4px 12px rgba(0,0,0,0.15);z-index:999999;cursor:pointer;transition:all 0.3s ease;';
+        bar.textContent = '● Live';
+        bar.onclick = () => bar.style.opacity = bar.style.opacity === '0.3' ? '1' : '0.3';
+        (document.body || document.documentElement).appendChild(bar);
+    }
+    if (page === 'app') createBar();
+
+    function setStatus(text, color) {
+        if (!bar) return;
+        bar.textContent = '● ' + text;
+        bar.style.background = color;
+    }
+
+    function showStatus(status) {
+        if (status === 'building') setStatus('Building...', '#ff9800');
+        else if (status === 'success') setStatus('Live', '#4caf50');
+        else if (status === 'failed') setStatus('Error', '#f44336');
+    }
+
+    // A placeholder page is stale once the build it describes is over
+    function isStale(status) {
+        if (page === 'building') return status === 'success' || status === 'failed';
+        if (page === 'error') return status === 'success';
+        return false;
+    }
+
+    function checkStatus() {
+        fetch('/__shtml/status', { cache: 'no-store' })
+            .then(res => res.text())
+            .then(status => {
+                if (isStale(status)) {
+                    location.reload();
+                } else {
+                    showStatus(status);
+                }
+            })
+            .catch(() => {});
+    }
+
+    let connected = false;
+    let stopped = false;
+    const events = new EventSource('/__shtml/events');
+    events.onopen = () => {
+        // Reconnecting means the server restarted: whatever is shown is stale
+        if (connected) {
+            location.reload();
+            return;
+        }
+        connected = true;
+        console.log('✅ SHTML live reload connected');
+        // The build may have finished between serving this page and connecting
+        checkStatus();
+    };
+    events.onmessage = (e) => {
+        if (e.data === 'reload') {
+            console.log('🔄 SHTML build finished, reloading');
+            location.reload();
+        } else if (e.data === 'building') {
+            showStatus('building');
+        } else if (e.data === 'server-shutdown') {
+            // Keep retrying: if the server comes back, onopen reloads
+            stopped = true;
+            if (!bar) createBar();
+            setStatus('Server stopped', '#9e9e9e');
+        }
+    };
+    events.onerror = () => {
+        if (!stopped) setStatus('Offline', '#9e9e9e');
+    };
+})();
+"#;
+    let mut resp = Response::new(200).js(script);
+    resp.headers.insert("Cache-Control".into(), "no-cache".into());
+    resp
+}
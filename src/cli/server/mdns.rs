@@ -0,0 +1,39 @@
+// DNS-SD advertisement of the dev server as `<project>.local`.
+
+use crate::network;
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+use std::{io, net::IpAddr, sync::Mutex, time::Duration};
+
+const WITHDRAW_TIMEOUT: Duration = Duration::from_secs(1);
+
+// The responder and the full name of the service it announces
+static ADVERTISED: Mutex<Option<(ServiceDaemon, String)>> = Mutex::new(None);
+
+/// Announces the server as `name` on `addresses`. Returns the host name it
+/// answers to, like `mysite.local`.
+pub fn advertise(name: &str, port: u16, https: bool, addresses: &[IpAddr]) -> io::Result<String> {
+    let label = network::dns_label(name).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("`{}` can't be a host name", name))
+    })?;
+    let host = format!("{}.local.", label);
+    let service = if https { "_https._tcp.local." } else { "_http._tcp.local." };
+    let info = ServiceInfo::new(service, name, &host, addresses, port, &[("path", "/")][..])
+        .map_err(io::Error::other)?;
+    let fullname = info.get_fullname().to_string();
+
+    let daemon = ServiceDaemon::new().map_err(io::Error::other)?;
+    daemon.register(info).map_err(io::Error::other)?;
+    *ADVERTISED.lock().unwrap() = Some((daemon, fullname));
+    Ok(host.trim_end_matches('.').to_string())
+}
+
+/// Sends the goodbye announcement, so browsers drop the service right away.
+pub fn withdraw() {
+    let Some((daemon, fullname)) = ADVERTISED.lock().unwrap().take() else {
+        return;
+    };
+    if let Ok(done) = daemon.unregister(&fullname) {
+        let _ = done.recv_timeout(WITHDRAW_TIMEOUT);
+    }
+    let _ = daemon.shutdown();
+}
@@ -0,0 +1,81 @@
+// Content types by file extension, extendable under `[mime]` in shtml.toml.
+
+use crate::config;
+
+const DEFAULT: &str = "application/octet-stream";
+
+const TYPES: &[(&str, &str)] = &[
+    // Documents and code
+    ("html", "text/html; charset=utf-8"),
+    ("htm", "text/html; charset=utf-8"),
+    ("css", "text/css; charset=utf-8"),
+    ("js", "application/javascript; charset=utf-8"),
+    ("mjs", "application/javascript; charset=utf-8"),
+    ("json", "application/json; charset=utf-8"),
+    ("map", "application/json; charset=utf-8"),
+    ("webmanifest", "application/manifest+json; charset=utf-8"),
+    ("xml", "application/xml; charset=utf-8"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("pdf", "application/pdf"),
+    ("wasm", "application/wasm"),
+    // Images
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("svg", "image/svg+xml"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("bmp", "image/bmp"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("ico", "image/x-icon"),
+    // Fonts
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("eot", "application/vnd.ms-fontobject"),
+    // Video
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("ogv", "video/ogg"),
+    ("mov", "video/quicktime"),
+    ("m4v", "video/x-m4v"),
+    // Audio
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("ogg", "audio/ogg"),
+    ("oga", "audio/ogg"),
+    ("m4a", "audio/mp4"),
+    ("aac", "audio/aac"),
+    ("flac", "audio/flac"),
+];
+
+/// Content type for a file name or path, matched case-insensitively on its
+/// extension. Project mappings from shtml.toml take precedence.
+pub fn content_type(name: &str) -> String {
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let Some((_, ext)) = file_name.rsplit_once('.') else {
+        return DEFAULT.to_string();
+    };
+    let ext = ext.to_ascii_lowercase();
+
+    let custom = config::get()
+        .mime
+        .iter()
+        .find(|(key, _)| key.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+        .map(|(_, value)| value.as_str());
+
+    custom
+        .or_else(|| {
+            TYPES
+                .iter()
+                .find(|(known, _)| *known == ext)
+                .map(|(_, value)| *value)
+        })
+        .unwrap_or(DEFAULT)
+        .to_string()
+}
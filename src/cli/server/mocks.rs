@@ -0,0 +1,163 @@
+// Mock API responses from fixture files (`shtml dev --mocks`).
+
+use super::handlers;
+use super::http::{self, Body, Request, Response};
+use super::mime;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    thread,
+    time::Duration,
+};
+
+const META_SUFFIX: &str = ".meta.toml";
+
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Meta {
+    status: Option<u16>,
+    /// Milliseconds to wait before responding
+    delay: u64,
+    headers: HashMap<String, String>,
+}
+
+/// Serves fixtures from `dir` for the rest of the process.
+pub fn enable(dir: impl Into<PathBuf>) {
+    if DIR.set(dir.into()).is_ok() {
+        handlers::register(|req: &Request| find(req).map(|fixture| serve(&fixture)));
+    }
+}
+
+/// The fixture answering `req`, if mocks are enabled and one exists.
+pub fn find(req: &Request) -> Option<PathBuf> {
+    let dir = DIR.get()?;
+    let segments: Vec<&str> = req.path.split('/').filter(|s| !s.is_empty()).collect();
+    if segments.iter().any(|s| *s == "." || *s == ".." || s.contains('\\')) {
+        return None;
+    }
+
+    // HEAD is answered from GET fixtures
+    let method = if req.method == "HEAD" { "GET" } else { req.method.as_str() };
+    [method, "ANY"]
+        .iter()
+        .find_map(|method| resolve(&dir.join(method), &segments))
+}
+
+// Walks the fixture tree one segment at a time; an exact name wins over a
+// `[param]` placeholder
+fn resolve(dir: &Path, segments: &[&str]) -> Option<PathBuf> {
+    let Some((last, parents)) = segments.split_last() else {
+        return file_for(dir, "index");
+    };
+
+    let mut candidates = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    for segment in parents {
+        dirs = dirs
+            .iter()
+            .flat_map(|dir| {
+                let exact = dir.join(segment);
+                let mut next: Vec<PathBuf> = exact.is_dir().then_some(exact).into_iter().collect();
+                next.extend(placeholders(dir).into_iter().filter(|p| p.is_dir()));
+                next
+            })
+            .collect();
+    }
+    for dir in &dirs {
+        candidates.push(file_for(dir, last));
+        candidates.push(file_for(&dir.join(last), "index"));
+        candidates.extend(
+            placeholders(dir)
+                .into_iter()
+                .map(|p| file_for_placeholder(&p)),
+        );
+    }
+    candidates.into_iter().flatten().next()
+}
+
+// `name` as requested, or `name.json`
+fn file_for(dir: &Path, name: &str) -> Option<PathBuf> {
+    [dir.join(name), dir.join(format!("{}.json", name))]
+        .into_iter()
+        .find(|p| p.is_file() && !is_meta(p))
+}
+
+fn file_for_placeholder(path: &Path) -> Option<PathBuf> {
+    if path.is_file() && !is_meta(path) {
+        return Some(path.to_path_buf());
+    }
+    file_for(path, "index").filter(|_| path.is_dir())
+}
+
+// Entries of `dir` named like `[id]` or `[id].json`, sorted for stable matches
+fn placeholders(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut found: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| name.starts_with('[') && name.contains(']'))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+fn is_meta(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(META_SUFFIX)
+}
+
+fn meta_path(fixture: &Path) -> PathBuf {
+    let name = fixture.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let stem = name.strip_suffix(".json").unwrap_or(name);
+    fixture.with_file_name(format!("{}{}", stem, META_SUFFIX))
+}
+
+fn serve(fixture: &Path) -> Response {
+    let meta = match read_meta(&meta_path(fixture)) {
+        Ok(meta) => meta,
+        Err(e) => return Response::new(500).text(&e),
+    };
+    let body = match fs::read(fixture) {
+        Ok(body) => body,
+        Err(e) => {
+            return Response::new(500).text(&format!("{}: {}", fixture.display(), e))
+        }
+    };
+
+    if meta.delay > 0 {
+        thread::sleep(Duration::from_millis(meta.delay));
+    }
+
+    let mut resp = Response::new(meta.status.unwrap_or(200));
+    resp.body = Body::Bytes(body);
+    resp.headers.insert(
+        "Content-Type".into(),
+        mime::content_type(&fixture.to_string_lossy()),
+    );
+    resp.headers.insert("Cache-Control".into(), "no-store".into());
+    resp.headers
+        .insert("X-Shtml-Mock".into(), fixture.display().to_string());
+    // Same spelling as ours, so `content-type` replaces rather than duplicates
+    for (name, value) in meta.headers {
+        resp.headers.insert(http::canonical_name(&name), value);
+    }
+    resp
+}
+
+fn read_meta(path: &Path) -> Result<Meta, String> {
+    if !path.exists() {
+        return Ok(Meta::default());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
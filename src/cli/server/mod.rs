@@ -0,0 +1,991 @@
+pub mod access;
+mod children;
+mod compress;
+mod events;
+pub mod handlers;
+mod http;
+mod inject;
+mod internal;
+pub mod mdns;
+mod mime;
+pub mod mocks;
+mod proxy;
+mod spa;
+mod static_files;
+pub mod tls;
+pub mod url;
+
+pub use http::{Request, Response};
+use events::broadcast;
+use static_files::{serve_not_found, serve_static_file, Resolved};
+use tls::Connection;
+
+use crate::dev_events::{self, BuildStep, DevEvent};
+use crate::host_files;
+use notify::{Config, RecursiveMode, Watcher};
+use std::{
+    fs,
+    io::{self, BufReader, Write},
+    net::TcpListener,
+    path::Path,
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::sync::{mpsc, watch, Semaphore};
+
+// Connections served at once; further ones wait in the listen backlog.
+// Live-reload streams don't count, they are cheap tasks.
+const MAX_CONNECTIONS: usize = 128;
+// Ports tried when the requested one is busy
+const PORT_ATTEMPTS: usize = 20;
+// How long shutdown waits for requests in flight
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
+
+lazy_static::lazy_static! {
+    static ref SHUTDOWN: watch::Sender<bool> = watch::channel(false).0;
+}
+
+#[derive(Debug, Clone)]
+enum BuildStatus {
+    Building,
+    Success,
+    Failed(String),
+}
+
+static BUILD_STATUS: Mutex<Option<BuildStatus>> = Mutex::new(None);
+
+// Methods the static files and the generated page answer to
+const STATIC_METHODS: &str = "GET, HEAD, OPTIONS";
+
+/// Serves until Ctrl+C, building in the background and rebuilding on
+/// changes. What happens is reported through `dev_events`.
+pub fn start_server(listener: TcpListener, tls: Option<Arc<rustls::ServerConfig>>) -> io::Result<()> {
+    start_builder(Arc::new(AtomicBool::new(false)));
+    serve(listener, tls, true)
+}
+
+/// Binds `host:port`. A busy port moves on to the next one, up to
+/// `PORT_ATTEMPTS` ports, unless `strict_port` is set.
+pub fn bind(host: &str, port: u16, strict_port: bool) -> io::Result<TcpListener> {
+    let attempts = if strict_port { 1 } else { PORT_ATTEMPTS };
+    let ports = (port..=u16::MAX).take(attempts);
+    let mut tried = 0;
+    for candidate in ports {
+        match TcpListener::bind((host, candidate)) {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => tried += 1,
+            Err(e) => return Err(io::Error::new(e.kind(), format!("{}:{}: {}", host, candidate, e))),
+        }
+    }
+    let message = if tried == 1 {
+        format!("port {} on {} is already in use", port, host)
+    } else {
+        format!("ports {}-{} on {} are all in use", port, port as usize + tried - 1, host)
+    };
+    Err(io::Error::new(io::ErrorKind::AddrInUse, message))
+}
+
+/// Stops accepting connections, tells open pages the server is going away,
+/// gives requests in flight a moment to finish, kills a running build and
+/// withdraws the mDNS advertisement. The file watcher stops and the running
+/// `start_server*` call returns.
+pub fn shutdown() {
+    SHUTDOWN.send_replace(true);
+    children::kill_all();
+    mdns::withdraw();
+}
+
+fn shutting_down() -> bool {
+    *SHUTDOWN.borrow()
+}
+
+// Blocks on an async runtime until `shutdown()`. Accepting and live-reload
+// streams are async; requests are answered by the blocking handler (files,
+// TLS, the proxy) on the runtime's bounded blocking pool.
+fn serve(
+    listener: TcpListener,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    stop_on_ctrl_c: bool,
+) -> io::Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("shtml-server")
+        .max_blocking_threads(MAX_CONNECTIONS)
+        .build()?;
+    // The TUI reads Ctrl+C as a key and shuts down itself
+    if stop_on_ctrl_c {
+        runtime.spawn(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                shutdown();
+            }
+        });
+    }
+    let result = runtime.block_on(accept_connections(listener, tls));
+    // Whatever is still running after the grace period is an idle keep-alive
+    runtime.shutdown_background();
+    result
+}
+
+// With a TLS config every connection is wrapped first
+async fn accept_connections(
+    listener: TcpListener,
+    tls: Option<Arc<rustls::ServerConfig>>,
+) -> io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    let limit = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+    let mut shutdown = SHUTDOWN.subscribe();
+    // Every connection task holds a sender, so `recv` on the other end returns
+    // once the last one is done
+    let (in_flight, mut all_done) = mpsc::channel::<()>(1);
+
+    loop {
+        let next = async {
+            let permit = limit.clone().acquire_owned().await.expect("semaphore is never closed");
+            listener.accept().await.map(|(stream, _)| (stream, permit))
+        };
+        let (stream, permit) = tokio::select! {
+            accepted = next => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    eprintln!("Accept error: {}", e);
+                    // Typically out of file descriptors; don't spin
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            _ = shutdown.wait_for(|stopped| *stopped) => break,
+        };
+
+        let tls = tls.clone();
+        let shutdown = shutdown.clone();
+        let in_flight = in_flight.clone();
+        tokio::spawn(async move {
+            let _in_flight = in_flight;
+            let https = tls.is_some();
+            let handled = tokio::task::spawn_blocking(move || {
+                let stream = stream.into_std()?;
+                stream.set_nonblocking(false)?;
+                let conn = Connection::accept(stream, tls.as_ref())?;
+                handle_connection(conn, https)
+            })
+            .await;
+            drop(permit);
+
+            let result = match handled {
+                Ok(Ok(Some(events))) => events::stream(events, shutdown).await,
+                Ok(Ok(None)) => Ok(()),
+                Ok(Err(e)) => Err(e),
+                Err(e) => Err(io::Error::other(e)),
+            };
+            match result {
+                // The client went away (a closed tab, an aborted media
+                // download) or never sent anything (an idle preconnect)
+                Err(e) if is_disconnect(&e) => {}
+                Err(e) => eprintln!("Connection error: {}", e),
+                Ok(()) => {}
+            }
+        });
+    }
+
+    // Let requests finish and event streams say goodbye
+    drop(in_flight);
+    let _ = tokio::time::timeout(SHUTDOWN_GRACE, all_done.recv()).await;
+    Ok(())
+}
+
+fn is_disconnect(e: &io::Error) -> bool {
+    http::is_timeout(e)
+        || matches!(
+            e.kind(),
+            io::ErrorKind::BrokenPipe
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+        )
+}
+
+fn set_build_status(status: BuildStatus) {
+    let building = matches!(status, BuildStatus::Building);
+    if let Ok(mut guard) = BUILD_STATUS.lock() {
+        *guard = Some(status);
+    }
+    // Let open pages show the build in progress; they reload once it's done
+    if building {
+        broadcast("building");
+    }
+}
+
+fn broadcast_reload() {
+    broadcast("reload");
+}
+
+fn get_build_status() -> Option<BuildStatus> {
+    BUILD_STATUS.lock().ok()?.clone()
+}
+
+// Errors are returned with the compiler or generator output. A build that
+// succeeded can still have a warning.
+fn build_swift_package() -> Result<Option<String>, String> {
+    // Ensure public directory exists
+    let _ = fs::create_dir_all("public");
+
+    dev_events::emit(DevEvent::BuildProgress { step: BuildStep::Compiling });
+    let output = children::output(Command::new("swift").arg("build"))
+        .map_err(|e| format!("Failed to run swift build: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Build Error:\n\n{}", command_output(&output)));
+    }
+    
+    // Run the executable to generate HTML (it writes to public/index.html via SHTML framework)
+    dev_events::emit(DevEvent::BuildProgress { step: BuildStep::Generating });
+    let output = children::output(Command::new("swift").arg("run"))
+        .map_err(|e| format!("Failed to run swift run: {}", e))?;
+    
+    if !output.status.success() {
+        return Err(format!("Generation Error:\n\n{}", command_output(&output)));
+    }
+    
+    // Copy Assets to public if they exist
+    if std::path::Path::new("Assets").exists() {
+        dev_events::emit(DevEvent::BuildProgress { step: BuildStep::CopyingAssets });
+        let _ = fs::remove_dir_all("public/Assets");
+        if let Err(e) = copy_dir_all("Assets", "public/Assets") {
+            return Ok(Some(format!("Failed to copy assets: {}", e)));
+        }
+    }
+
+    Ok(None)
+}
+
+fn command_output(output: &std::process::Output) -> String {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let text = [stderr.trim(), stdout.trim()]
+        .iter()
+        .filter(|s| !s.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if text.is_empty() {
+        "Failed with no output.".to_string()
+    } else {
+        text
+    }
+}
+
+// Serves requests until the connection closes. An event stream request hands
+// the connection back, to be streamed to asynchronously.
+fn handle_connection(stream: Connection, https: bool) -> io::Result<Option<Connection>> {
+    stream.tcp().set_nodelay(true)?;
+    stream.tcp().set_read_timeout(Some(Duration::from_secs(10)))?;
+    let peer = stream.tcp().peer_addr()?.ip();
+    // Requests are read and responses written through the same buffered
+    // stream, which is all a TLS session offers
+    let mut reader = BufReader::new(stream);
+
+    for served in 1..=http::MAX_REQUESTS_PER_CONNECTION {
+        let mut req = match http::read_request(&mut reader) {
+            Ok(Some(r)) => r,
+            Ok(None) => return Ok(None),
+            // Idle persistent connection
+            Err(e) if http::is_timeout(&e) => return Ok(None),
+            Err(_) => {
+                // Also where rejected TLS handshakes end up (untrusted
+                // certificate), so a failed write isn't worth reporting
+                let _ = http::write_response(reader.get_mut(), Response::new(400).text("Bad Request"), None);
+                return Ok(None);
+            }
+        };
+        let started = Instant::now();
+
+        // Plain HTTP on the HTTPS port: only the CA is served, so phones can
+        // fetch it before they trust it
+        if https && !reader.get_ref().is_tls() && req.path != internal::CA_CERT {
+            let host = req.header("host").unwrap_or("localhost");
+            let mut resp = Response::new(308).text("Use HTTPS");
+            resp.headers
+                .insert("Location".into(), format!("https://{}{}", host, request_uri(&req)));
+            http::write_response(reader.get_mut(), resp, Some(&req))?;
+            return Ok(None);
+        }
+
+        if let Some(resp) = access::check(&req, peer) {
+            http::write_response(reader.get_mut(), resp, Some(&req))?;
+            return Ok(None);
+        }
+
+        if req.path == internal::EVENTS {
+            return Ok(Some(reader.into_inner()));
+        }
+
+        // Fixtures stand in for the backend, so they win over proxy rules
+        let proxy_rule = (!internal::is_internal(&req.path) && mocks::find(&req).is_none())
+            .then(|| proxy::find_rule(&req.path))
+            .flatten();
+        let (mut resp, body_consumed) = match proxy_rule {
+            Some(rule) => proxy::forward(&req, &mut reader, rule),
+            None => {
+                // Don't invite a body that is going to be refused anyway
+                if req.expects_continue()
+                    && req.content_length().is_none_or(|len| len <= http::MAX_BODY_BYTES)
+                {
+                    let conn = reader.get_mut();
+                    conn.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+                    conn.flush()?;
+                }
+                match http::read_body(&mut reader, &mut req) {
+                    Ok(()) => (handle_request(&req), true),
+                    Err(http::BodyError::TooLarge) => (
+                        Response::new(413).text(&format!(
+                            "Request body larger than {} MB",
+                            http::MAX_BODY_BYTES / (1024 * 1024)
+                        )),
+                        false,
+                    ),
+                    Err(http::BodyError::Malformed) => {
+                        (Response::new(400).text("Bad Request"), false)
+                    }
+                    Err(http::BodyError::Io(e)) => return Err(e),
+                }
+            }
+        };
+        let keep_alive = body_consumed
+            && req.wants_keep_alive()
+            && served < http::MAX_REQUESTS_PER_CONNECTION;
+
+        if keep_alive {
+            resp.headers.insert("Connection".into(), "keep-alive".into());
+            resp.headers.insert(
+                "Keep-Alive".into(),
+                format!(
+                    "timeout={}, max={}",
+                    http::KEEP_ALIVE_TIMEOUT.as_secs(),
+                    http::MAX_REQUESTS_PER_CONNECTION - served
+                ),
+            );
+        }
+        let status = resp.status;
+        let written = http::write_response(reader.get_mut(), resp, Some(&req))?;
+        // The path only: a query may carry the access token
+        dev_events::emit(DevEvent::Request {
+            method: req.method.clone(),
+            path: req.path.clone(),
+            status,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+        if !written {
+            return Ok(None);
+        }
+
+        reader.get_ref().tcp().set_read_timeout(Some(http::KEEP_ALIVE_TIMEOUT))?;
+    }
+
+    Ok(None)
+}
+
+// Path plus query, as sent by the client
+fn request_uri(req: &Request) -> String {
+    match &req.query {
+        Some(query) => format!("{}?{}", url::encode_path(&req.path), query),
+        None => url::encode_path(&req.path),
+    }
+}
+
+fn handle_request(req: &Request) -> Response {
+    if internal::is_internal(&req.path) {
+        return internal::handle(req);
+    }
+
+    if let Some(resp) = handlers::dispatch(req) {
+        return resp;
+    }
+
+    // Everything below is read-only
+    match req.method.as_str() {
+        "GET" | "HEAD" => {}
+        "OPTIONS" => {
+            let mut resp = Response::new(204);
+            resp.headers.insert("Allow".into(), STATIC_METHODS.into());
+            return resp;
+        }
+        _ => {
+            let mut resp = Response::new(405).text("Method Not Allowed");
+            resp.headers.insert("Allow".into(), STATIC_METHODS.into());
+            return resp;
+        }
+    }
+
+    let mut resp = apply_redirects(req).unwrap_or_else(|| serve_path(req));
+    apply_custom_headers(&req.path, &mut resp);
+    resp
+}
+
+// The first matching `_redirects` rule, as a redirect or a rewritten response.
+// Like on the host, a rule is shadowed by an existing file unless forced.
+fn apply_redirects(req: &Request) -> Option<Response> {
+    let rules = host_files::load_redirects();
+    if rules.is_empty() {
+        return None;
+    }
+    let file_exists =
+        req.path == "/" || !matches!(static_files::resolve(&req.path), Resolved::Missing);
+
+    for rule in rules {
+        if file_exists && !rule.force {
+            continue;
+        }
+        let Some(target) = rule.target(&req.path) else {
+            continue;
+        };
+
+        if rule.is_redirect() {
+            let location = match &req.query {
+                Some(query) if !target.contains('?') => format!("{}?{}", target, query),
+                _ => target,
+            };
+            let mut resp = Response::new(rule.status).text(&format!("Redirecting to {}", location));
+            resp.headers.insert("Location".into(), location);
+            resp.headers.insert("Cache-Control".into(), "no-cache".into());
+            return Some(resp);
+        }
+
+        // Rewrites to other sites are left to the host
+        if !target.starts_with('/') {
+            continue;
+        }
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (target.as_str(), req.query.clone()),
+        };
+        let mut rewritten = req.clone();
+        rewritten.path = url::percent_decode(path).unwrap_or_else(|| path.to_string());
+        rewritten.query = query;
+        let mut resp = serve_path(&rewritten);
+        if resp.status == 200 {
+            resp.status = rule.status;
+        }
+        return Some(resp);
+    }
+    None
+}
+
+fn apply_custom_headers(path: &str, resp: &mut Response) {
+    for rule in host_files::load_headers() {
+        if rule.matches(path) {
+            for (name, value) in &rule.headers {
+                resp.headers.insert(http::canonical_name(name), value.clone());
+            }
+        }
+    }
+}
+
+fn serve_path(req: &Request) -> Response {
+    if req.path == "/" {
+        return serve_generated_html();
+    }
+
+    match static_files::resolve(&req.path) {
+        Resolved::File(path) => serve_static_file(req, &path),
+        Resolved::Page(path) => serve_html_page(&path),
+        Resolved::Redirect(path) => {
+            let mut canonical = req.clone();
+            canonical.path = path;
+            let location = request_uri(&canonical);
+            let mut resp = Response::new(301).text(&format!("Redirecting to {}", location));
+            resp.headers.insert("Location".into(), location);
+            resp
+        }
+        // SPA fallback: serve generated HTML for client-side routes
+        Resolved::Missing if spa::serves_page(&req.path) => serve_generated_html(),
+        Resolved::Missing => serve_not_found(),
+    }
+}
+
+fn serve_generated_html() -> Response {
+    serve_html_page(Path::new("public/index.html"))
+}
+
+// Any generated page, with the live-reload client injected
+fn serve_html_page(path: &Path) -> Response {
+    // Check build status first
+    match get_build_status() {
+        Some(BuildStatus::Building) => serve_building_page(),
+        Some(BuildStatus::Failed(error)) => serve_error_page(&error),
+        _ => {
+            match fs::read_to_string(path) {
+                Ok(html) => {
+                    let mut resp = Response::new(200).html(&inject::inject_client(&html, "app"));
+                    resp.headers.insert("Cache-Control".into(), "no-cache".into());
+                    resp
+                }
+                Err(_) => serve_no_output_page(),
+            }
+        }
+    }
+}
+
+fn serve_building_page() -> Response {
+    Response::new(200).html(&inject::inject_client(r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Building - SHTML</title>
+    <style>
+        * { margin: 0; padding: 0; box-sizing: border-box; }
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
+            display: flex;
+            align-items: center;
+            justify-content: center;
+            min-height: 100vh;
+            background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
+            color: white;
+        }
+        .container {
+            text-align: center;
+            max-width: 600px;
+            padding: 60px 40px;
+            background: rgba(255, 255, 255, 0.1);
+            backdrop-filter: blur(20px);
+            border-radius: 24px;
+            box-shadow: 0 8px 32px rgba(0, 0, 0, 0.2);
+        }
+        .spinner {
+            width: 60px;
+            height: 60px;
+            border: 4px solid rgba(255, 255, 255, 0.3);
+            border-top-color: white;
+            border-radius: 50%;
+            animation: spin 1s linear infinite;
+            margin: 0 auto 30px;
+        }
+        @keyframes spin {
+            to { transform: rotate(360deg); }
+        }
+        h1 {
+            font-size: 2.5em;
+            margin-bottom: 20px;
+            font-weight: 700;
+        }
+        p {
+            font-size: 1.2em;
+            opacity: 0.9;
+            line-height: 1.6;
+        }
+        .dots::after {
+            content: '';
+            animation: dots 1.5s steps(4, end) infinite;
+        }
+        @keyframes dots {
+            0%, 20% { content: ''; }
+            40% { content: '.'; }
+            60% { content: '..'; }
+            80%, 100% { content: '...'; }
+        }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="spinner"></div>
+        <h1>⚡ Building</h1>
+        <p>Compiling Swift code<span class="dots"></span></p>
+        <p style="margin-top: 20px; opacity: 0.7; font-size: 0.9em;">
+            This page will automatically refresh when ready
+        </p>
+    </div>
+</body>
+</html>
+"#, "building"))
+}
+
+fn serve_error_page(error: &str) -> Response {
+    let error_html = html_escape(error);
+    Response::new(200).html(&inject::inject_client(&format!(r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Build Error - SHTML</title>
+    <style>
+        * {{ margin: 0; padding: 0; box-sizing: border-box; }}
+        body {{
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, monospace;
+            background: #1e1e1e;
+            color: #d4d4d4;
+            padding: 40px 20px;
+            min-height: 100vh;
+        }}
+        .container {{
+            max-width: 1200px;
+            margin: 0 auto;
+        }}
+        .header {{
+            background: #ff3b30;
+            color: white;
+            padding: 30px;
+            border-radius: 12px 12px 0 0;
+            display: flex;
+            align-items: center;
+            gap: 20px;
+        }}
+        .header h1 {{
+            font-size: 2em;
+            font-weight: 700;
+        }}
+        .icon {{
+            font-size: 3em;
+        }}
+        .error-content {{
+            background: #2d2d2d;
+            padding: 30px;
+            border-radius: 0 0 12px 12px;
+            overflow-x: auto;
+        }}
+        pre {{
+            background: #1e1e1e;
+            padding: 20px;
+            border-radius: 8px;
+            overflow-x: auto;
+            line-height: 1.6;
+            border-left: 4px solid #ff3b30;
+            font-size: 14px;
+            font-family: 'SF Mono', Monaco, 'Cascadia Code', 'Roboto Mono', Consolas, monospace;
+        }}
+        .error-line {{
+            color: #ff6b6b;
+            font-weight: bold;
+        }}
+        .help {{
+            margin-top: 30px;
+            padding: 20px;
+            background: rgba(102, 126, 234, 0.1);
+            border-radius: 8px;
+            border-left: 4px solid #667eea;
+        }}
+        .help h2 {{
+            color: #667eea;
+            margin-bottom: 15px;
+            font-size: 1.2em;
+        }}
+        .help ul {{
+            list-style-position: inside;
+            line-height: 2;
+        }}
+        .watching {{
+            margin-top: 20px;
+            padding: 15px;
+            background: rgba(255, 152, 0, 0.1);
+            border-radius: 8px;
+            border-left: 4px solid #ff9800;
+            text-align: center;
+            font-weight: 600;
+            color: #ff9800;
+        }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <div class="icon">❌</div>
+            <div>
+                <h1>Build Failed</h1>
+                <p>Fix the errors below and save to rebuild</p>
+            </div>
+        </div>
+        <div class="error-content">
+            <pre>{}</pre>
+            
+            <div class="help">
+                <h2>💡 Quick Tips</h2>
+                <ul>
+                    <li>Check for syntax errors in your Swift code</li>
+                    <li>Make sure all imports are correct</li>
+                    <li>Verify that all types conform to required protocols</li>
+                    <li>The server will auto-rebuild when you save changes</li>
+                </ul>
+            </div>
+            
+            <div class="watching">
+                👀 Watching for changes - will auto-rebuild and refresh
+            </div>
+        </div>
+    </div>
+</body>
+</html>
+"#, error_html), "error"))
+}
+
+fn serve_no_output_page() -> Response {
+    Response::new(200).html(&inject::inject_client(r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>No Output - SHTML</title>
+    <style>
+        * { margin: 0; padding: 0; box-sizing: border-box; }
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
+            display: flex;
+            align-items: center;
+            justify-content: center;
+            min-height: 100vh;
+            background: linear-gradient(135deg, #f093fb 0%, #f5576c 100%);
+            color: white;
+            padding: 20px;
+        }
+        .container {
+            text-align: center;
+            max-width: 600px;
+            padding: 60px 40px;
+            background: rgba(255, 255, 255, 0.1);
+            backdrop-filter: blur(20px);
+            border-radius: 24px;
+        }
+        h1 {
+            font-size: 3em;
+            margin-bottom: 20px;
+        }
+        p {
+            font-size: 1.2em;
+            line-height: 1.8;
+            opacity: 0.9;
+        }
+        code {
+            background: rgba(0, 0, 0, 0.2);
+            padding: 4px 8px;
+            border-radius: 4px;
+            font-family: monospace;
+        }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>📭</h1>
+        <h1>No HTML Output</h1>
+        <p style="margin-top: 30px;">
+            The build succeeded but no HTML file was found at <code>public/index.html</code>
+        </p>
+        <p style="margin-top: 20px;">
+            Make sure your code calls <code>.generate()</code> on your Website
+        </p>
+    </div>
+</body>
+</html>
+"#, "no-output"))
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+// TUI-integrated server
+/// Like `start_server`, but Ctrl+C is left to the TUI, which also asks for
+/// rebuilds through `rebuild_flag`.
+pub fn start_server_with_tui(
+    listener: TcpListener,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    rebuild_flag: Arc<AtomicBool>,
+) -> io::Result<()> {
+    start_builder(rebuild_flag);
+    serve(listener, tls, false)
+}
+
+// Initial build and file watcher. The server is already up, so pages opened
+// meanwhile get the building page and refresh when it's done.
+fn start_builder(rebuild_flag: Arc<AtomicBool>) {
+    internal::mark_started();
+    thread::spawn(move || {
+        rebuild(None);
+        if let Err(e) = start_file_watcher(rebuild_flag) {
+            eprintln!("File watcher error: {}", e);
+        }
+    });
+}
+
+// Runs one build, reporting it as events and keeping the served pages in
+// sync with it
+fn rebuild(changed: Option<String>) {
+    if let Some(path) = changed {
+        dev_events::emit(DevEvent::FileChanged { path });
+    }
+    dev_events::emit(DevEvent::BuildStart);
+    set_build_status(BuildStatus::Building);
+
+    let start = Instant::now();
+    let result = build_swift_package();
+    // A build killed on quit isn't a failure worth showing
+    if shutting_down() {
+        return;
+    }
+
+    let duration_ms = start.elapsed().as_millis() as u64;
+    match result {
+        Ok(warning) => {
+            set_build_status(BuildStatus::Success);
+            dev_events::emit(DevEvent::BuildFinished {
+                success: true,
+                duration_ms,
+                size: fs::metadata("public/index.html").ok().map(|m| m.len()),
+                diagnostics: warning,
+            });
+        }
+        Err(error) => {
+            set_build_status(BuildStatus::Failed(error.clone()));
+            dev_events::emit(DevEvent::BuildFinished {
+                success: false,
+                duration_ms,
+                size: None,
+                diagnostics: Some(error),
+            });
+        }
+    }
+    broadcast_reload();
+}
+
+fn start_file_watcher(rebuild_flag: Arc<AtomicBool>) -> notify::Result<()> {
+    use notify::{Event, PollWatcher};
+    
+    let (tx, rx) = std::sync::mpsc::channel::<notify::Result<Event>>();
+    
+    // Use PollWatcher with 50ms polling - very aggressive to catch changes immediately
+    let config = Config::default()
+        .with_poll_interval(Duration::from_millis(50));
+    
+    let mut watcher = PollWatcher::new(tx, config)?;
+    watcher.watch(Path::new("Sources"), RecursiveMode::Recursive)?;
+    
+    // Keep watcher alive
+    let _watcher_handle = watcher;
+    
+    let last_build = Arc::new(Mutex::new(std::time::Instant::now()));
+    let last_mtime = Arc::new(Mutex::new(std::time::SystemTime::UNIX_EPOCH));
+    
+    while !shutting_down() {
+        // Check manual rebuild flag
+        if rebuild_flag.swap(false, Ordering::SeqCst) {
+            rebuild(None);
+        }
+        
+        // Backup: Check file modification times directly (in case FS events are delayed)
+        if let Ok(entries) = fs::read_dir("Sources") {
+            let mut latest_mtime = std::time::SystemTime::UNIX_EPOCH;
+            
+            for entry in entries.flatten() {
+                if let Ok(metadata) = entry.metadata() {
+                    if let Ok(mtime) = metadata.modified() {
+                        if mtime > latest_mtime {
+                            latest_mtime = mtime;
+                        }
+                    }
+                }
+                
+                // Check subdirectories recursively
+                if entry.path().is_dir() {
+                    if let Ok(sub_entries) = fs::read_dir(entry.path()) {
+                        for sub_entry in sub_entries.flatten() {
+                            if let Ok(metadata) = sub_entry.metadata() {
+                                if let Ok(mtime) = metadata.modified() {
+                                    if mtime > latest_mtime {
+                                        latest_mtime = mtime;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            
+            let mut last_known = last_mtime.lock().unwrap();
+            if latest_mtime > *last_known && latest_mtime > std::time::SystemTime::UNIX_EPOCH {
+                *last_known = latest_mtime;
+                drop(last_known);
+                
+                // Trigger rebuild
+                let mut last = last_build.lock().unwrap();
+                let now = std::time::Instant::now();
+                
+                if now.duration_since(*last) >= Duration::from_millis(300) {
+                    *last = now;
+                    drop(last);
+                    
+                    rebuild(Some("Source files".to_string()));
+                }
+            }
+        }
+        
+        // Check file system events
+        match rx.recv_timeout(Duration::from_millis(50)) {
+            Ok(Ok(event)) => {
+                let should_skip = event.paths.iter().any(|path| {
+                    let path_str = path.to_string_lossy();
+                    path_str.ends_with(".swp") 
+                        || path_str.ends_with("~") 
+                        || path_str.contains(".git")
+                        || path_str.contains("/.build/")
+                });
+                
+                if should_skip {
+                    continue;
+                }
+                
+                let mut last = last_build.lock().unwrap();
+                let now = std::time::Instant::now();
+                
+                if now.duration_since(*last) < Duration::from_millis(300) {
+                    continue;
+                }
+                
+                *last = now;
+                drop(last);
+                
+                let changed = event
+                    .paths
+                    .first()
+                    .map(|path| path.to_string_lossy().to_string());
+                rebuild(changed);
+            }
+            Ok(Err(e)) => {
+                eprintln!("Watch error: {:?}", e);
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                // Continue polling
+            }
+            Err(e) => {
+                eprintln!("Channel error: {:?}", e);
+                break;
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+
+fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
+    fs::create_dir_all(&dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let ty = entry.file_type()?;
+        if ty.is_dir() {
+            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
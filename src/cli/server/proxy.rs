@@ -0,0 +1,287 @@
+// Reverse proxy for the `[[dev.proxy]]` rules in shtml.toml.
+
+use super::http::{self, Body, ChunkedReader, Request, Response};
+use super::tls::Connection;
+use super::url;
+use crate::config::{self, ProxyRule};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Meaningful for a single connection only, never forwarded
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "expect",
+];
+
+pub fn find_rule(path: &str) -> Option<&'static ProxyRule> {
+    config::get()
+        .dev
+        .proxy
+        .iter()
+        .find(|rule| config::path_matches(&rule.path, path))
+}
+
+/// Forwards `req` (whose body is still unread in `client`) and returns the
+/// upstream response, or a 502 page. The flag tells whether the request body
+/// was consumed, i.e. whether the client connection can be reused.
+pub fn forward(
+    req: &Request,
+    client: &mut BufReader<Connection>,
+    rule: &ProxyRule,
+) -> (Response, bool) {
+    let (authority, base_path) = split_target(&rule.target);
+
+    let mut upstream = match connect(authority) {
+        Ok(stream) => stream,
+        Err(e) => {
+            // The request body is never read, so don't reuse the connection
+            let unread = req.content_length().is_some_and(|len| len > 0) || req.is_chunked();
+            return (bad_gateway(&rule.target, &e), !unread);
+        }
+    };
+
+    // Wherever it failed, the request body may be half read
+    if let Err(e) = send_request(req, client, &mut upstream, authority, base_path) {
+        return (bad_gateway(&rule.target, &e), false);
+    }
+
+    match read_response(req, upstream) {
+        Ok(resp) => (resp, true),
+        Err(e) => (bad_gateway(&rule.target, &e), true),
+    }
+}
+
+// "http://host:port/base" -> ("host:port", "/base")
+fn split_target(target: &str) -> (&str, &str) {
+    let rest = target.strip_prefix("http://").unwrap_or(target);
+    match rest.find('/') {
+        Some(i) => (&rest[..i], rest[i..].trim_end_matches('/')),
+        None => (rest, ""),
+    }
+}
+
+fn connect(authority: &str) -> io::Result<TcpStream> {
+    let with_port = if authority.rsplit_once(':').is_some_and(|(_, p)| p.parse::<u16>().is_ok()) {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no address");
+    for addr in with_port.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_nodelay(true)?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn send_request(
+    req: &Request,
+    client: &mut BufReader<Connection>,
+    upstream: &mut TcpStream,
+    authority: &str,
+    base_path: &str,
+) -> io::Result<()> {
+    let mut target = format!("{}{}", base_path, url::encode_path(&req.path));
+    if let Some(query) = &req.query {
+        target.push('?');
+        target.push_str(query);
+    }
+
+    let dropped = connection_tokens(req.header("connection"));
+    let mut head = format!("{} {} HTTP/1.1\r\n", req.method, target);
+    for (name, value) in &req.headers {
+        if name == "host"
+            || name == "content-length"
+            || name.starts_with("x-forwarded-")
+            || HOP_BY_HOP.contains(&name.as_str())
+            || dropped.contains(name)
+        {
+            continue;
+        }
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!("Host: {}\r\n", authority));
+    let conn = client.get_ref();
+    if let Ok(peer) = conn.tcp().peer_addr() {
+        head.push_str(&format!("X-Forwarded-For: {}\r\n", peer.ip()));
+    }
+    if let Some(host) = req.header("host") {
+        head.push_str(&format!("X-Forwarded-Host: {}\r\n", host));
+    }
+    let proto = if conn.is_tls() { "https" } else { "http" };
+    head.push_str(&format!("X-Forwarded-Proto: {}\r\n", proto));
+    head.push_str("Connection: close\r\n");
+
+    let chunked = req.is_chunked();
+    let length = match req.header("content-length") {
+        Some(len) if !chunked => Some(len.parse::<u64>().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "bad Content-Length")
+        })?),
+        _ => None,
+    };
+    if chunked {
+        head.push_str("Transfer-Encoding: chunked\r\n");
+    } else if let Some(len) = length {
+        head.push_str(&format!("Content-Length: {}\r\n", len));
+    }
+    head.push_str("\r\n");
+    upstream.write_all(head.as_bytes())?;
+
+    // The client may be waiting for permission to send the body
+    if (chunked || length.is_some_and(|len| len > 0)) && req.expects_continue() {
+        let conn = client.get_mut();
+        conn.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+        conn.flush()?;
+    }
+
+    if chunked {
+        http::write_chunked(upstream, &mut ChunkedReader::new(&mut *client))?;
+    } else if let Some(len) = length {
+        let copied = io::copy(&mut client.by_ref().take(len), upstream)?;
+        if copied != len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "request body shorter than Content-Length",
+            ));
+        }
+    }
+    upstream.flush()
+}
+
+fn read_response(req: &Request, upstream: TcpStream) -> io::Result<Response> {
+    let mut reader = BufReader::new(upstream);
+
+    // Interim responses (100 Continue, 103 Early Hints) aren't relayed
+    let (status, headers) = loop {
+        let (status, headers) = read_response_head(&mut reader)?;
+        if !(100..200).contains(&status) {
+            break (status, headers);
+        }
+    };
+
+    let dropped = connection_tokens(
+        headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("connection"))
+            .map(|(_, value)| value.as_str()),
+    );
+    let mut resp = Response::new(status);
+    let mut length = None;
+    let mut chunked = false;
+    for (name, value) in headers {
+        let lower = name.to_ascii_lowercase();
+        if lower == "content-length" {
+            length = value.parse::<u64>().ok();
+            continue;
+        }
+        if lower == "transfer-encoding" {
+            chunked = value.to_ascii_lowercase().contains("chunked");
+            continue;
+        }
+        if HOP_BY_HOP.contains(&lower.as_str()) || dropped.contains(&lower) {
+            continue;
+        }
+        let name = http::canonical_name(&lower);
+        if lower == "set-cookie" || resp.headers.contains_key(&name) {
+            resp.extra_headers.push((name, value));
+        } else {
+            resp.headers.insert(name, value);
+        }
+    }
+
+    let has_body = req.method != "HEAD" && !matches!(status, 204 | 304);
+    resp.body = if !has_body {
+        // Keep the upstream length for HEAD; write_response sends no body
+        Body::Stream {
+            reader: Box::new(io::empty()),
+            len: length.filter(|_| req.method == "HEAD"),
+        }
+    } else if chunked {
+        Body::Stream {
+            reader: Box::new(ChunkedReader::new(reader)),
+            len: None,
+        }
+    } else if let Some(len) = length {
+        Body::Stream {
+            reader: Box::new(reader.take(len)),
+            len: Some(len),
+        }
+    } else {
+        // Delimited by the upstream closing the connection
+        Body::Stream {
+            reader: Box::new(reader),
+            len: None,
+        }
+    };
+    Ok(resp)
+}
+
+fn read_response_head<R: BufRead>(reader: &mut R) -> io::Result<(u16, Vec<(String, String)>)> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let version = parts.next().unwrap_or("");
+    let status = parts.next().and_then(|s| s.parse::<u16>().ok());
+    let status = match status {
+        Some(status) if version.starts_with("HTTP/1.") => status,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "upstream sent an invalid response",
+            ))
+        }
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "eof"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    Ok((status, headers))
+}
+
+// Extra hop-by-hop headers named in a Connection header
+fn connection_tokens(connection: Option<&str>) -> Vec<String> {
+    connection
+        .unwrap_or("")
+        .split(',')
+        .map(|t| t.trim().to_ascii_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+fn bad_gateway(target: &str, error: &io::Error) -> Response {
+    let mut resp = Response::new(502).text(&format!(
+        "SHTML dev server: proxy to {} failed: {}",
+        target, error
+    ));
+    resp.headers.insert("Cache-Control".into(), "no-store".into());
+    resp
+}
@@ -0,0 +1,64 @@
+// SPA fallback: which unknown paths get the generated page.
+
+use super::inject::Tags;
+use crate::config::{self, SpaFallback};
+use std::fs;
+
+pub fn serves_page(path: &str) -> bool {
+    match config::get().dev.spa_fallback {
+        SpaFallback::On => !path.rsplit('/').next().unwrap_or("").contains('.'),
+        SpaFallback::Off => false,
+        SpaFallback::Routes => match fs::read_to_string("public/index.html") {
+            Ok(html) => router_paths(&html)
+                .iter()
+                .any(|route| route_matches(route, path)),
+            // Nothing generated yet: let the building/error page through
+            Err(_) => true,
+        },
+    }
+}
+
+fn router_paths(html: &str) -> Vec<String> {
+    Tags::new(html)
+        .filter(|tag| !tag.closing)
+        .filter(|tag| {
+            tag.attr("class")
+                .is_some_and(|class| class.split_whitespace().any(|c| c == "route"))
+        })
+        .filter_map(|tag| tag.attr("data-path").map(str::to_string))
+        .collect()
+}
+
+fn normalize(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else if path.len() > 1 {
+        path.strip_suffix('/').unwrap_or(path)
+    } else {
+        path
+    }
+}
+
+fn route_matches(route: &str, path: &str) -> bool {
+    let route = normalize(route);
+    let path = normalize(path);
+    if route == path {
+        return true;
+    }
+    if let Some(prefix) = route.strip_suffix('*') {
+        if path.starts_with(normalize(prefix)) {
+            return true;
+        }
+    }
+
+    let route_segments: Vec<&str> = route.split('/').filter(|s| !s.is_empty()).collect();
+    let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    route_segments.len() == path_segments.len()
+        && route_segments
+            .iter()
+            .zip(&path_segments)
+            .all(|(route, path)| match route.strip_prefix(':') {
+                Some(name) => !name.is_empty(),
+                None => route == path,
+            })
+}
@@ -0,0 +1,558 @@
+// Static files from `public/`, with validators, Cache-Control and ranges.
+
+use super::{
+    http::{Body, Request, Response},
+    inject, mime,
+};
+use crate::config;
+use chrono::{DateTime, Utc};
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const DEFAULT_CACHE_CONTROL: &str = "no-cache";
+
+// Maps a decoded URL path to a file inside `root`. Both sides are
+// canonicalised, so `..` segments and symlinks can't escape the root.
+fn resolve_under(root: &Path, path: &str) -> Option<PathBuf> {
+    let root = fs::canonicalize(root).ok()?;
+    let relative = path.trim_start_matches('/');
+    let resolved = fs::canonicalize(root.join(relative)).ok()?;
+    resolved.starts_with(&root).then_some(resolved)
+}
+
+pub enum Resolved {
+    File(PathBuf),
+    /// An HTML page, served with the live-reload client
+    Page(PathBuf),
+    /// The canonical URL path for a directory or pretty URL
+    Redirect(String),
+    Missing,
+}
+
+/// Maps a URL path to what `public/` has for it, the way static hosts do:
+/// `/blog/` serves blog/index.html, `/about` serves about.html, and `/blog`
+/// and `/about/` redirect to those canonical forms.
+pub fn resolve(path: &str) -> Resolved {
+    resolve_in(Path::new("public"), path)
+}
+
+fn resolve_in(root: &Path, path: &str) -> Resolved {
+    if let Some(found) = resolve_under(root, path) {
+        if !found.is_dir() {
+            return if is_html(&found) {
+                Resolved::Page(found)
+            } else {
+                Resolved::File(found)
+            };
+        }
+        if found.join("index.html").is_file() {
+            if !path.ends_with('/') {
+                return Resolved::Redirect(format!("{}/", path));
+            }
+            return Resolved::Page(found.join("index.html"));
+        }
+        // No index: about.html may sit next to an about/ of sub-pages
+    }
+
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() || trimmed.ends_with(".html") {
+        return Resolved::Missing;
+    }
+    match resolve_under(root, &format!("{}.html", trimmed)) {
+        Some(page) if page.is_file() && path.ends_with('/') => {
+            Resolved::Redirect(trimmed.to_string())
+        }
+        Some(page) if page.is_file() => Resolved::Page(page),
+        _ => Resolved::Missing,
+    }
+}
+
+fn is_html(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"))
+}
+
+pub fn serve_static_file(req: &Request, path: &Path) -> Response {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return serve_not_found(),
+    };
+
+    let modified = metadata.modified().ok();
+    let etag = etag(&metadata);
+    let cache_control = cache_control_for(&req.path);
+
+    if is_not_modified(req, &etag, modified) {
+        let mut resp = Response::new(304);
+        set_validators(&mut resp, &etag, modified, &cache_control);
+        return resp;
+    }
+
+    let len = metadata.len();
+    let content_type = mime::content_type(&path.to_string_lossy());
+
+    let mut resp = match requested_ranges(req, len, &etag, modified) {
+        Ranges::Full => match open_range(path, 0, len) {
+            Ok(reader) => {
+                let mut resp = Response::new(200);
+                resp.body = Body::Stream {
+                    reader,
+                    len: Some(len),
+                };
+                resp
+            }
+            Err(_) => return serve_not_found(),
+        },
+        Ranges::Unsatisfiable => {
+            let mut resp = Response::new(416);
+            resp.headers
+                .insert("Content-Range".into(), format!("bytes */{}", len));
+            resp
+        }
+        Ranges::Partial(ranges) => match partial_content(path, &ranges, len, &content_type) {
+            Ok(resp) => resp,
+            Err(_) => return serve_not_found(),
+        },
+    };
+
+    set_validators(&mut resp, &etag, modified, &cache_control);
+    resp.headers.insert("Accept-Ranges".into(), "bytes".into());
+    resp.headers
+        .entry("Content-Type".to_string())
+        .or_insert(content_type);
+    resp.headers
+        .insert("X-Content-Type-Options".into(), "nosniff".into());
+    resp
+}
+
+#[derive(Debug, PartialEq)]
+enum Ranges {
+    Full,
+    Unsatisfiable,
+    /// Inclusive byte ranges, in the order they were requested
+    Partial(Vec<(u64, u64)>),
+}
+
+// More ranges than this is not a media player seeking; just send the file
+const MAX_RANGES: usize = 16;
+
+fn requested_ranges(req: &Request, len: u64, etag: &str, modified: Option<SystemTime>) -> Ranges {
+    if req.method != "GET" && req.method != "HEAD" {
+        return Ranges::Full;
+    }
+    let Some(header) = req.header("range") else {
+        return Ranges::Full;
+    };
+
+    // If-Range: only honour the range if the client's copy is still current
+    if let Some(if_range) = req.header("if-range") {
+        let if_range = if_range.trim();
+        let current = if if_range.starts_with('"') || if_range.starts_with("W/") {
+            // Weak validators never match for ranges
+            if_range == etag
+        } else {
+            match (DateTime::parse_from_rfc2822(if_range), modified) {
+                (Ok(date), Some(modified)) => {
+                    DateTime::<Utc>::from(modified).timestamp() == date.timestamp()
+                }
+                _ => false,
+            }
+        };
+        if !current {
+            return Ranges::Full;
+        }
+    }
+
+    // Syntactically invalid ranges are ignored, as if there was no header
+    let Some(specs) = header.trim().strip_prefix("bytes=") else {
+        return Ranges::Full;
+    };
+    let mut ranges = Vec::new();
+    for spec in specs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let Some((start, end)) = spec.split_once('-') else {
+            return Ranges::Full;
+        };
+        let range = match (start.trim(), end.trim()) {
+            // Suffix range: the last N bytes
+            ("", suffix) => match suffix.parse::<u64>() {
+                Ok(0) => None,
+                Ok(n) => Some((len.saturating_sub(n), len.saturating_sub(1))),
+                Err(_) => return Ranges::Full,
+            },
+            (start, end) => {
+                let Ok(start) = start.parse::<u64>() else {
+                    return Ranges::Full;
+                };
+                let end = if end.is_empty() {
+                    u64::MAX
+                } else {
+                    match end.parse::<u64>() {
+                        Ok(end) if end >= start => end,
+                        _ => return Ranges::Full,
+                    }
+                };
+                (start < len).then(|| (start, end.min(len - 1)))
+            }
+        };
+        // Unsatisfiable parts are dropped; only all of them failing is an error
+        if let Some(range) = range.filter(|_| len > 0) {
+            ranges.push(range);
+        }
+    }
+
+    if ranges.is_empty() {
+        Ranges::Unsatisfiable
+    } else if ranges.len() > MAX_RANGES {
+        Ranges::Full
+    } else {
+        Ranges::Partial(ranges)
+    }
+}
+
+// Streams `len` bytes from `start` instead of reading the file into memory
+fn open_range(path: &Path, start: u64, len: u64) -> io::Result<Box<dyn Read + Send>> {
+    let mut file = File::open(path)?;
+    if start > 0 {
+        file.seek(SeekFrom::Start(start))?;
+    }
+    Ok(Box::new(file.take(len)))
+}
+
+fn partial_content(
+    path: &Path,
+    ranges: &[(u64, u64)],
+    total: u64,
+    content_type: &str,
+) -> io::Result<Response> {
+    let mut resp = Response::new(206);
+
+    if let [(start, end)] = ranges {
+        let len = end - start + 1;
+        resp.body = Body::Stream {
+            reader: open_range(path, *start, len)?,
+            len: Some(len),
+        };
+        resp.headers.insert(
+            "Content-Range".into(),
+            format!("bytes {}-{}/{}", start, end, total),
+        );
+        return Ok(resp);
+    }
+
+    // multipart/byteranges: every part gets its own small header
+    let boundary = format!(
+        "shtml-{:x}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    );
+    let mut body: Box<dyn Read + Send> = Box::new(io::empty());
+    let mut body_len = 0;
+    for (start, end) in ranges {
+        let part_head = format!(
+            "--{}\r\nContent-Type: {}\r\nContent-Range: bytes {}-{}/{}\r\n\r\n",
+            boundary, content_type, start, end, total
+        );
+        let len = end - start + 1;
+        let reader = open_range(path, *start, len)?;
+        body_len += part_head.len() as u64 + len + 2;
+        body = Box::new(
+            body.chain(io::Cursor::new(part_head))
+                .chain(reader)
+                .chain(&b"\r\n"[..]),
+        );
+    }
+    let closing = format!("--{}--\r\n", boundary);
+    body_len += closing.len() as u64;
+    body = Box::new(body.chain(io::Cursor::new(closing)));
+
+    resp.body = Body::Stream {
+        reader: body,
+        len: Some(body_len),
+    };
+    resp.headers.insert(
+        "Content-Type".into(),
+        format!("multipart/byteranges; boundary={}", boundary),
+    );
+    Ok(resp)
+}
+
+// mtime + size, like most servers: cheap for large videos and changes
+// whenever the build rewrites the file
+fn etag(metadata: &Metadata) -> String {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .unwrap_or_default();
+    format!(
+        "\"{:x}.{:x}-{:x}\"",
+        mtime.as_secs(),
+        mtime.subsec_nanos(),
+        metadata.len()
+    )
+}
+
+fn set_validators(
+    resp: &mut Response,
+    etag: &str,
+    modified: Option<SystemTime>,
+    cache_control: &str,
+) {
+    resp.headers.insert("ETag".into(), etag.to_string());
+    if let Some(modified) = modified {
+        resp.headers.insert("Last-Modified".into(), http_date(modified));
+    }
+    resp.headers
+        .insert("Cache-Control".into(), cache_control.to_string());
+}
+
+fn cache_control_for(path: &str) -> String {
+    config::get()
+        .dev
+        .cache_control
+        .iter()
+        .find(|rule| config::path_matches(&rule.pattern, path))
+        .map_or(DEFAULT_CACHE_CONTROL, |rule| rule.value.as_str())
+        .to_string()
+}
+
+fn is_not_modified(req: &Request, etag: &str, modified: Option<SystemTime>) -> bool {
+    if req.method != "GET" && req.method != "HEAD" {
+        return false;
+    }
+
+    // If-None-Match takes precedence; If-Modified-Since is ignored when present
+    if let Some(if_none_match) = req.header("if-none-match") {
+        return if_none_match.trim() == "*"
+            || if_none_match
+                .split(',')
+                .any(|tag| weak_eq(tag.trim(), etag));
+    }
+
+    match (req.header("if-modified-since"), modified) {
+        (Some(since), Some(modified)) => match DateTime::parse_from_rfc2822(since) {
+            // HTTP dates have one-second resolution
+            Ok(since) => DateTime::<Utc>::from(modified).timestamp() <= since.timestamp(),
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+pub fn http_date(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// The project's `public/404.html` if there is one, otherwise a built-in page.
+pub fn serve_not_found() -> Response {
+    if let Ok(html) = fs::read_to_string("public/404.html") {
+        let mut resp = Response::new(404).html(&inject::inject_client(&html, "app"));
+        resp.headers.insert("Cache-Control".into(), "no-cache".into());
+        return resp;
+    }
+
+    Response::new(404).html(r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>404 - Not Found</title>
+    <style>
+        body { font-family: system-ui; display: flex; align-items: center; justify-content: center; height: 100vh; margin: 0; background: #1a1a1a; color: #fff; }
+        .container { text-align: center; }
+        h1 { font-size: 72px; margin: 0; }
+        p { font-size: 24px; color: #888; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>404</h1>
+        <p>File Not Found</p>
+    </div>
+</body>
+</html>
+"#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh site root with `files` in it
+    fn site(name: &str, files: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("shtml-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x").unwrap();
+        }
+        root
+    }
+
+    fn describe(resolved: Resolved, root: &Path) -> String {
+        let root = fs::canonicalize(root).unwrap();
+        let relative = |path: PathBuf| path.strip_prefix(&root).unwrap().display().to_string();
+        match resolved {
+            Resolved::File(path) => format!("file {}", relative(path)),
+            Resolved::Page(path) => format!("page {}", relative(path)),
+            Resolved::Redirect(to) => format!("redirect {}", to),
+            Resolved::Missing => "missing".to_string(),
+        }
+    }
+
+    #[test]
+    fn resolves_like_static_hosts() {
+        let root = site("resolve", &["index.html", "blog/index.html", "about.html", "logo.png"]);
+        let cases = [
+            ("/", "page index.html"),
+            ("/blog/", "page blog/index.html"),
+            ("/blog", "redirect /blog/"),
+            ("/about", "page about.html"),
+            ("/about/", "redirect /about"),
+            ("/about.html", "page about.html"),
+            ("/logo.png", "file logo.png"),
+            ("/missing", "missing"),
+            ("/../etc/passwd", "missing"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(describe(resolve_in(&root, path), &root), expected, "{}", path);
+        }
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn serves_page_next_to_folder_without_index() {
+        let root = site("sibling", &["docs.html", "docs/team.html"]);
+        assert_eq!(describe(resolve_in(&root, "/docs"), &root), "page docs.html");
+        assert_eq!(describe(resolve_in(&root, "/docs/"), &root), "redirect /docs");
+        assert_eq!(describe(resolve_in(&root, "/docs/team"), &root), "page docs/team.html");
+        let _ = fs::remove_dir_all(root);
+    }
+
+    fn request(method: &str, headers: &[(&str, &str)]) -> Request {
+        Request {
+            method: method.to_string(),
+            path: "/video.mp4".to_string(),
+            query: None,
+            version: "HTTP/1.1".to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: Vec::new(),
+        }
+    }
+
+    fn ranges(range: &str, len: u64) -> Ranges {
+        requested_ranges(&request("GET", &[("range", range)]), len, "\"abc\"", None)
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(ranges("bytes=0-99", 1000), Ranges::Partial(vec![(0, 99)]));
+        assert_eq!(ranges("bytes=900-", 1000), Ranges::Partial(vec![(900, 999)]));
+        assert_eq!(ranges("bytes=-100", 1000), Ranges::Partial(vec![(900, 999)]));
+        // Suffix longer than the file, end past the end: clamped
+        assert_eq!(ranges("bytes=-5000", 1000), Ranges::Partial(vec![(0, 999)]));
+        assert_eq!(ranges("bytes=990-5000", 1000), Ranges::Partial(vec![(990, 999)]));
+        assert_eq!(
+            ranges("bytes=0-0, -1", 1000),
+            Ranges::Partial(vec![(0, 0), (999, 999)])
+        );
+    }
+
+    #[test]
+    fn drops_unsatisfiable_ranges() {
+        assert_eq!(ranges("bytes=1000-", 1000), Ranges::Unsatisfiable);
+        assert_eq!(ranges("bytes=-0", 1000), Ranges::Unsatisfiable);
+        assert_eq!(ranges("bytes=0-10", 0), Ranges::Unsatisfiable);
+        // Only the satisfiable part is served
+        assert_eq!(ranges("bytes=2000-3000, 0-9", 1000), Ranges::Partial(vec![(0, 9)]));
+    }
+
+    #[test]
+    fn ignores_invalid_ranges() {
+        for range in ["items=0-9", "bytes=5-1", "bytes=a-9", "bytes=0-9,x", "bytes=--1"] {
+            assert_eq!(ranges(range, 1000), Ranges::Full, "{}", range);
+        }
+        let req = request("POST", &[("range", "bytes=0-9")]);
+        assert_eq!(requested_ranges(&req, 1000, "\"abc\"", None), Ranges::Full);
+    }
+
+    #[test]
+    fn sends_whole_file_for_too_many_ranges() {
+        let many: Vec<String> = (0..=MAX_RANGES).map(|i| format!("{}-{}", i * 10, i * 10 + 1)).collect();
+        assert_eq!(ranges(&format!("bytes={}", many.join(",")), 1000), Ranges::Full);
+        let max = &many[..MAX_RANGES];
+        assert!(matches!(ranges(&format!("bytes={}", max.join(",")), 1000), Ranges::Partial(r) if r.len() == MAX_RANGES));
+    }
+
+    #[test]
+    fn honours_if_range() {
+        let modified = UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        let date = http_date(modified);
+        let cases = [
+            ("\"abc\"", true),
+            ("\"old\"", false),
+            ("W/\"abc\"", false),
+            (date.as_str(), true),
+            ("Tue, 14 Nov 2023 22:13:21 GMT", false),
+            ("not a date", false),
+        ];
+        for (if_range, honoured) in cases {
+            let req = request("GET", &[("range", "bytes=0-9"), ("if-range", if_range)]);
+            let expected = if honoured { Ranges::Partial(vec![(0, 9)]) } else { Ranges::Full };
+            assert_eq!(requested_ranges(&req, 1000, "\"abc\"", Some(modified)), expected, "{}", if_range);
+        }
+    }
+
+    fn body(resp: Response) -> (Vec<u8>, Option<u64>) {
+        let Body::Stream { mut reader, len } = resp.body else {
+            panic!("expected a streamed body");
+        };
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).unwrap();
+        (bytes, len)
+    }
+
+    #[test]
+    fn serves_partial_content() {
+        let root = site("ranges", &[]);
+        fs::create_dir_all(&root).unwrap();
+        let file = root.join("data.txt");
+        fs::write(&file, "0123456789abcdefghij").unwrap();
+
+        let resp = partial_content(&file, &[(2, 5)], 20, "text/plain").unwrap();
+        assert_eq!(resp.headers["Content-Range"], "bytes 2-5/20");
+        assert_eq!(body(resp), (b"2345".to_vec(), Some(4)));
+
+        let resp = partial_content(&file, &[(0, 1), (18, 19)], 20, "text/plain").unwrap();
+        let content_type = resp.headers["Content-Type"].clone();
+        let boundary = content_type.strip_prefix("multipart/byteranges; boundary=").unwrap();
+        let (bytes, len) = body(resp);
+        let expected = format!(
+            "--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/20\r\n\r\n01\r\n\
+             --{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 18-19/20\r\n\r\nij\r\n\
+             --{b}--\r\n",
+            b = boundary
+        );
+        assert_eq!(String::from_utf8(bytes.clone()).unwrap(), expected);
+        // The declared length frames the body on keep-alive connections
+        assert_eq!(len, Some(bytes.len() as u64));
+        let _ = fs::remove_dir_all(root);
+    }
+}
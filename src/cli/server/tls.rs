@@ -0,0 +1,296 @@
+// Local HTTPS for `shtml dev --https`, signed by a CA in ~/.shtml/certs.
+
+use rcgen::{
+    BasicConstraints, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, Issuer, KeyPair,
+    KeyUsagePurpose,
+};
+use rustls::{
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    ServerConfig, ServerConnection, StreamOwned,
+};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+const CA_NAME: &str = "SHTML Development CA";
+const CA_CERT: &str = "ca.pem";
+const CA_KEY: &str = "ca-key.pem";
+const SERVER_CERT: &str = "server.pem";
+const SERVER_KEY: &str = "server-key.pem";
+// The names the current server certificate was issued for, one per line
+const SERVER_NAMES: &str = "server.names";
+
+const CA_VALID_DAYS: i64 = 10 * 365;
+// Apple platforms reject server certificates valid for more than 398 days
+const SERVER_VALID_DAYS: i64 = 397;
+const SERVER_RENEW_AFTER: Duration = Duration::from_secs(300 * 24 * 60 * 60);
+
+// First byte of a TLS record carrying a handshake
+const TLS_HANDSHAKE: u8 = 0x16;
+
+static CA_PEM: OnceLock<String> = OnceLock::new();
+
+pub struct Tls {
+    pub config: Arc<ServerConfig>,
+    /// Where the CA certificate lives, for the trust instructions
+    pub ca_path: PathBuf,
+}
+
+/// A client connection, either plain or wrapped in TLS.
+pub enum Connection {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ServerConnection, TcpStream>>),
+}
+
+impl Connection {
+    /// Wraps an accepted socket. Plain HTTP sent to an HTTPS port is passed
+    /// through so it can be redirected (and so phones can fetch the CA).
+    pub fn accept(stream: TcpStream, tls: Option<&Arc<ServerConfig>>) -> io::Result<Self> {
+        let Some(config) = tls else {
+            return Ok(Connection::Plain(stream));
+        };
+        let mut first = [0u8; 1];
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        if stream.peek(&mut first)? == 1 && first[0] != TLS_HANDSHAKE {
+            return Ok(Connection::Plain(stream));
+        }
+        let conn = ServerConnection::new(config.clone()).map_err(io::Error::other)?;
+        Ok(Connection::Tls(Box::new(StreamOwned::new(conn, stream))))
+    }
+
+    pub fn tcp(&self) -> &TcpStream {
+        match self {
+            Connection::Plain(stream) => stream,
+            Connection::Tls(tls) => &tls.sock,
+        }
+    }
+
+    pub fn is_tls(&self) -> bool {
+        matches!(self, Connection::Tls(_))
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.read(buf),
+            Connection::Tls(tls) => tls.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.write(buf),
+            Connection::Tls(tls) => tls.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.flush(),
+            Connection::Tls(tls) => tls.flush(),
+        }
+    }
+}
+
+/// The local CA certificate (PEM), once HTTPS has been set up.
+pub fn ca_certificate() -> Option<&'static str> {
+    CA_PEM.get().map(String::as_str)
+}
+
+/// Loads or creates the CA and a server certificate covering `names`
+/// (host names and IP addresses).
+pub fn setup(names: &[String]) -> io::Result<Tls> {
+    let dir = cert_dir()?;
+    create_private_dir(&dir)?;
+
+    let (ca_params, ca_key) = load_or_create_ca(&dir)?;
+    let ca_pem = fs::read_to_string(dir.join(CA_CERT))?;
+
+    if !server_cert_is_current(&dir, names) {
+        issue_server_cert(&dir, names, ca_params, &ca_key)?;
+    }
+
+    let chain = CertificateDer::pem_file_iter(dir.join(SERVER_CERT))
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(pem_error)?;
+    let key = PrivateKeyDer::from_pem_file(dir.join(SERVER_KEY)).map_err(pem_error)?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_no_client_auth()
+        .with_single_cert(chain, key)
+        .map_err(io::Error::other)?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    CA_PEM.get_or_init(|| ca_pem);
+    Ok(Tls {
+        config: Arc::new(config),
+        ca_path: dir.join(CA_CERT),
+    })
+}
+
+/// Where devices download the CA from `server_url`. Plain HTTP: a phone
+/// can't open the HTTPS URL before it trusts the CA.
+pub fn ca_url(server_url: &str) -> String {
+    let base = server_url.strip_prefix("https://").unwrap_or(server_url);
+    format!("http://{}{}", base, super::internal::CA_CERT)
+}
+
+pub fn print_trust_instructions(ca_path: &Path, url: &str) {
+    let ca = ca_path.display();
+    let ca_url = ca_url(url);
+    println!("🔒 HTTPS uses a local certificate authority: {}", ca);
+    println!("   Trust it once on each device to get rid of certificate warnings:");
+    println!("   macOS    sudo security add-trusted-cert -d -r trustRoot -k /Library/Keychains/System.keychain \"{}\"", ca);
+    println!("   Linux    sudo cp \"{}\" /usr/local/share/ca-certificates/shtml-dev-ca.crt && sudo update-ca-certificates", ca);
+    println!("   Windows  certutil -addstore -f ROOT \"{}\"", ca);
+    println!("   Firefox  Settings → Privacy & Security → Certificates → View Certificates → Import");
+    println!("   iOS      open {} in Safari, install the profile in Settings,", ca_url);
+    println!("            then enable it under General → About → Certificate Trust Settings");
+    println!("   Android  open {}, then Settings → Security → Install a certificate → CA certificate", ca_url);
+    println!();
+}
+
+fn cert_dir() -> io::Result<PathBuf> {
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "could not find the home directory"))?;
+    Ok(PathBuf::from(home).join(".shtml").join("certs"))
+}
+
+// The CA parameters are rebuilt rather than parsed back: issuing only needs
+// the subject name and the key, and both are stable.
+fn ca_params() -> CertificateParams {
+    let mut params = CertificateParams::default();
+    params.distinguished_name.push(DnType::CommonName, CA_NAME);
+    params.distinguished_name.push(DnType::OrganizationName, "SHTML");
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.key_usages = vec![
+        KeyUsagePurpose::KeyCertSign,
+        KeyUsagePurpose::CrlSign,
+        KeyUsagePurpose::DigitalSignature,
+    ];
+    params
+}
+
+fn load_or_create_ca(dir: &Path) -> io::Result<(CertificateParams, KeyPair)> {
+    let cert_path = dir.join(CA_CERT);
+    let key_path = dir.join(CA_KEY);
+
+    if cert_path.exists() && key_path.exists() {
+        let key = KeyPair::from_pem(&fs::read_to_string(&key_path)?).map_err(cert_error)?;
+        return Ok((ca_params(), key));
+    }
+
+    let key = KeyPair::generate().map_err(cert_error)?;
+    let mut params = ca_params();
+    set_validity(&mut params, CA_VALID_DAYS);
+    let cert = params.self_signed(&key).map_err(cert_error)?;
+
+    write_private(&key_path, &key.serialize_pem())?;
+    fs::write(&cert_path, cert.pem())?;
+    Ok((ca_params(), key))
+}
+
+fn server_cert_is_current(dir: &Path, names: &[String]) -> bool {
+    let Ok(issued_for) = fs::read_to_string(dir.join(SERVER_NAMES)) else {
+        return false;
+    };
+    let covered = names
+        .iter()
+        .all(|name| issued_for.lines().any(|line| line == name));
+    let fresh = fs::metadata(dir.join(SERVER_CERT))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < SERVER_RENEW_AFTER);
+    covered && fresh && dir.join(SERVER_KEY).exists()
+}
+
+fn issue_server_cert(
+    dir: &Path,
+    names: &[String],
+    ca_params: CertificateParams,
+    ca_key: &KeyPair,
+) -> io::Result<()> {
+    let mut params = CertificateParams::new(names.to_vec()).map_err(cert_error)?;
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "SHTML development server");
+    params.key_usages = vec![
+        KeyUsagePurpose::DigitalSignature,
+        KeyUsagePurpose::KeyEncipherment,
+    ];
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+    params.use_authority_key_identifier_extension = true;
+    set_validity(&mut params, SERVER_VALID_DAYS);
+
+    let key = KeyPair::generate().map_err(cert_error)?;
+    let issuer = Issuer::new(ca_params, ca_key);
+    let cert = params.signed_by(&key, &issuer).map_err(cert_error)?;
+
+    write_private(&dir.join(SERVER_KEY), &key.serialize_pem())?;
+    fs::write(dir.join(SERVER_CERT), cert.pem())?;
+    fs::write(dir.join(SERVER_NAMES), names.join("\n"))
+}
+
+fn set_validity(params: &mut CertificateParams, days: i64) {
+    use chrono::Datelike;
+    let day = |date: chrono::DateTime<chrono::Utc>| {
+        rcgen::date_time_ymd(date.year(), date.month() as u8, date.day() as u8)
+    };
+    let now = chrono::Utc::now();
+    // Start yesterday so devices with a slightly wrong clock still accept it
+    params.not_before = day(now - chrono::Duration::days(1));
+    params.not_after = day(now + chrono::Duration::days(days));
+}
+
+// Only the user may read the keys: the CA is trusted system-wide
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        builder.mode(0o700).create(dir)?;
+        // Also tightens a directory created with default permissions
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    #[cfg(not(unix))]
+    builder.create(dir)?;
+    Ok(())
+}
+
+fn write_private(path: &Path, content: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // `mode` only applies to new files; an existing one is emptied by now,
+    // so tightening it before writing exposes nothing
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())
+}
+
+fn cert_error(e: rcgen::Error) -> io::Error {
+    io::Error::other(format!("certificate generation failed: {}", e))
+}
+
+fn pem_error(e: rustls::pki_types::pem::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid certificate file: {}", e))
+}
@@ -0,0 +1,103 @@
+// Request-target parsing.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    /// Percent-decoded path, always starting with '/'
+    pub path: String,
+    /// Raw query string without the leading '?'
+    pub query: Option<String>,
+}
+
+pub fn parse_target(raw: &str) -> Option<Target> {
+    let origin_form = if raw.starts_with('/') {
+        raw
+    } else {
+        let (scheme, rest) = raw.split_once("://")?;
+        if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)) {
+            return None;
+        }
+        rest.find(['/', '?']).map_or("/", |i| &rest[i..])
+    };
+
+    // Fragments are never sent by browsers, but be lenient with other clients
+    let without_fragment = origin_form.split('#').next().unwrap_or("");
+    let (path, query) = match without_fragment.split_once('?') {
+        Some((path, query)) => (path, Some(query.to_string())),
+        None => (without_fragment, None),
+    };
+
+    let path = if path.is_empty() { "/" } else { path };
+    let decoded = percent_decode(path)?;
+    // Decoded paths end up in headers (redirects) and file names
+    if decoded.chars().any(char::is_control) || !decoded.starts_with('/') {
+        return None;
+    }
+
+    Some(Target {
+        path: decoded,
+        query,
+    })
+}
+
+/// Decodes `%XX` escapes. Returns `None` for malformed escapes or if the
+/// result isn't valid UTF-8.
+pub fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hi = hex_value(*bytes.get(i + 1)?)?;
+            let lo = hex_value(*bytes.get(i + 2)?)?;
+            out.push(hi << 4 | lo);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Encodes a decoded path for use in a URL (e.g. a `Location` header),
+/// leaving '/' and the characters that are safe in a path segment alone.
+pub fn encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for &b in path.as_bytes() {
+        if b.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_path_and_keeps_query() {
+        let target = parse_target("http://example.com/caf%C3%A9?a=%20#top").unwrap();
+        assert_eq!(target.path, "/café");
+        assert_eq!(target.query.as_deref(), Some("a=%20"));
+    }
+
+    #[test]
+    fn rejects_control_characters() {
+        for raw in ["/a%00b", "/a%0D%0ASet-Cookie:%20x", "/a%7F", "/%C2%85"] {
+            assert!(parse_target(raw).is_none(), "{}", raw);
+        }
+        assert!(parse_target("/a%zz").is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use crossterm::{
+    cursor,
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -11,8 +12,14 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
     Frame, Terminal,
 };
+use crate::browser;
+use crate::dev_events::{self, DevEvent, NetworkUrl};
+use crate::network::{self, Address};
+use crate::server::tls;
 use std::{
     io,
+    net::SocketAddr,
+    panic,
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -30,35 +37,162 @@ pub struct AppState {
     pub last_build_time: Option<String>,
     pub file_changed: Option<String>,
     pub server_url: String,
-    pub local_ip: String,
+    /// Where other devices can reach the server; the QR code shows one
+    pub addresses: Vec<Address>,
+    pub qr_address: usize,
     pub port: u16,
+    /// The port asked for; differs from `port` when it was busy
+    pub requested_port: u16,
+    pub https: bool,
+    /// Where the local CA lives when serving HTTPS
+    pub ca_path: Option<String>,
+    /// Required from other devices; part of the QR code URL
+    pub token: Option<String>,
+    /// Something that went wrong outside a build, like a browser not opening
+    pub notice: Option<String>,
+    /// A problem with the last build that didn't fail it, like assets that
+    /// couldn't be copied
+    pub build_warning: Option<String>,
+    /// Set by `--open`; cleared once the browser has been opened
+    pub open_after_build: bool,
+    // Listening on loopback or everywhere, so `localhost` reaches the server
+    local: bool,
 }
 
 impl AppState {
-    pub fn new(port: u16) -> Self {
-        let local_ip = get_local_ip().unwrap_or_else(|| "127.0.0.1".to_string());
-        Self {
+    pub fn new(addr: SocketAddr, requested_port: u16, https: bool) -> Self {
+        // Listening everywhere: offer the addresses other devices can reach
+        let mut addresses = if addr.ip().is_unspecified() {
+            // IPv6 only when bound to `::`; `0.0.0.0` doesn't accept it
+            let mut addresses = network::lan_addresses(addr.is_ipv6());
+            if !addresses.is_empty() {
+                addresses.extend(network::mdns_hostname().map(|name| Address::new("mDNS", name)));
+            }
+            addresses
+        } else {
+            vec![Address::new("listening on", addr.ip().to_string())]
+        };
+        if addresses.is_empty() {
+            // Offline: at least this machine's browser can connect
+            addresses.push(Address::new("loopback", "127.0.0.1"));
+        }
+
+        let mut state = Self {
             build_state: BuildState::Idle,
             last_build_time: None,
             file_changed: None,
-            server_url: format!("http://{}:{}", local_ip, port),
-            local_ip,
-            port,
+            server_url: String::new(),
+            addresses,
+            qr_address: 0,
+            port: addr.port(),
+            requested_port,
+            https,
+            ca_path: None,
+            token: None,
+            notice: None,
+            build_warning: None,
+            open_after_build: false,
+            local: addr.ip().is_unspecified() || addr.ip().is_loopback(),
+        };
+        state.server_url = state.url_for(&state.addresses[0]);
+        state
+    }
+
+    fn url_for(&self, address: &Address) -> String {
+        let scheme = if self.https { "https" } else { "http" };
+        format!("{}://{}:{}", scheme, address.url_host(), self.port)
+    }
+
+    fn with_token(&self, url: String) -> String {
+        match &self.token {
+            Some(token) => format!("{}/?token={}", url, token),
+            None => url,
         }
     }
-}
 
-fn get_local_ip() -> Option<String> {
-    use std::net::UdpSocket;
-    
-    // Try to get local network IP by connecting to a public DNS
-    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
-    socket.connect("8.8.8.8:80").ok()?;
-    let addr = socket.local_addr().ok()?;
-    Some(addr.ip().to_string())
+    /// The URL to open on this machine. `localhost` where it works: it needs
+    /// no token and is in the HTTPS certificate.
+    pub fn browser_url(&self) -> String {
+        if self.local {
+            let scheme = if self.https { "https" } else { "http" };
+            format!("{}://localhost:{}", scheme, self.port)
+        } else {
+            self.with_token(self.server_url.clone())
+        }
+    }
+
+    /// The URL the QR code encodes.
+    pub fn qr_url(&self) -> String {
+        self.network_url(&self.addresses[self.qr_address])
+    }
+
+    /// The URL other devices open to reach the server at `address`.
+    pub fn network_url(&self, address: &Address) -> String {
+        self.with_token(self.url_for(address))
+    }
+
+    pub fn next_qr_address(&mut self) {
+        self.qr_address = (self.qr_address + 1) % self.addresses.len();
+    }
+
+    /// The `server-ready` event for the addresses shown here.
+    pub fn ready_event(&self) -> DevEvent {
+        DevEvent::ServerReady {
+            local_url: self.browser_url(),
+            network: self
+                .addresses
+                .iter()
+                .map(|address| NetworkUrl {
+                    interface: address.interface.clone(),
+                    url: self.network_url(address),
+                })
+                .collect(),
+            ca_path: self.ca_path.clone(),
+            notice: self.notice.clone(),
+        }
+    }
+
+    /// Updates the state for what the dev server reported.
+    pub fn apply(&mut self, event: &DevEvent) {
+        match event {
+            DevEvent::FileChanged { path } => self.file_changed = Some(path.clone()),
+            DevEvent::BuildStart => self.build_state = BuildState::Building,
+            DevEvent::BuildFinished {
+                success: true,
+                duration_ms,
+                size,
+                diagnostics,
+            } => {
+                self.build_warning = diagnostics.clone();
+                self.build_state = BuildState::Success {
+                    duration: *duration_ms as f32 / 1000.0,
+                    size: size.map_or_else(|| "N/A".to_string(), dev_events::format_size),
+                };
+                self.last_build_time = Some(chrono::Local::now().format("%H:%M:%S").to_string());
+            }
+            DevEvent::BuildFinished { diagnostics, .. } => {
+                self.build_warning = None;
+                self.build_state = BuildState::Failed {
+                    error: diagnostics.clone().unwrap_or_default(),
+                };
+            }
+            _ => {}
+        }
+    }
 }
 
 pub fn run_tui(state: Arc<Mutex<AppState>>, trigger_rebuild: Arc<dyn Fn() + Send + Sync>) -> io::Result<()> {
+    // A panic in the TUI would otherwise leave the shell in raw mode on the
+    // alternate screen, with the panic message drawn over by nothing
+    let tui_thread = std::thread::current().id();
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if std::thread::current().id() == tui_thread {
+            let _ = restore_terminal();
+        }
+        default_hook(info);
+    }));
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -68,14 +202,7 @@ pub fn run_tui(state: Arc<Mutex<AppState>>, trigger_rebuild: Arc<dyn Fn() + Send
 
     let res = run_app(&mut terminal, state, trigger_rebuild);
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    restore_terminal()?;
 
     if let Err(err) = res {
         println!("Error: {:?}", err);
@@ -84,6 +211,27 @@ pub fn run_tui(state: Arc<Mutex<AppState>>, trigger_rebuild: Arc<dyn Fn() + Send
     Ok(())
 }
 
+/// Opens the site in a browser in the background; a failure shows up in the
+/// status panel.
+pub fn open_browser(state: &Arc<Mutex<AppState>>) {
+    let state = state.clone();
+    std::thread::spawn(move || {
+        let Ok(url) = state.lock().map(|s| s.browser_url()) else {
+            return;
+        };
+        if let Err(e) = browser::open(&url) {
+            if let Ok(mut s) = state.lock() {
+                s.notice = Some(format!("Couldn't open a browser: {}", e));
+            }
+        }
+    });
+}
+
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, cursor::Show)
+}
+
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     state: Arc<Mutex<AppState>>,
@@ -140,20 +288,15 @@ fn run_app<B: Backend>(
                     KeyCode::Char('r') => {
                         trigger_rebuild();
                     }
+                    KeyCode::Char('a') => {
+                        if let Ok(mut s) = state.lock() {
+                            s.next_qr_address();
+                        }
+                    }
                     KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                         return Ok(false);
                     }
-                    KeyCode::Char('b') => {
-                        // Open browser
-                        if let Ok(s) = state.lock() {
-                            let url = s.server_url.clone();
-                            std::thread::spawn(move || {
-                                let _ = std::process::Command::new("open")
-                                    .arg(&url)
-                                    .spawn();
-                            });
-                        }
-                    }
+                    KeyCode::Char('b') => open_browser(&state),
                     _ => {}
                 }
             }
@@ -200,6 +343,12 @@ fn render_status(f: &mut Frame, area: Rect, state: &AppState) {
         BuildState::Failed { .. } => ("❌ Build Failed", Color::Red),
     };
 
+    // The error page has the whole output; the first compiler error is enough here
+    let failure = match &state.build_state {
+        BuildState::Failed { error } => error.lines().map(str::trim).find(|line| line.contains("error:")),
+        _ => None,
+    };
+
     let mut lines = vec![
         Line::from(vec![
             Span::styled("Status: ", Style::default().fg(Color::Gray)),
@@ -211,6 +360,53 @@ fn render_status(f: &mut Frame, area: Rect, state: &AppState) {
         ]),
     ];
 
+    if let Some(failure) = failure {
+        lines.push(Line::from(Span::styled(failure, Style::default().fg(Color::Red))));
+    }
+
+    if state.port != state.requested_port {
+        lines.push(Line::from(Span::styled(
+            format!("Port {} was busy, using {}", state.requested_port, state.port),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
+    if let Some(warning) = &state.build_warning {
+        lines.push(Line::from(Span::styled(
+            format!("⚠️  {}", warning),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
+    if let Some(notice) = &state.notice {
+        lines.push(Line::from(Span::styled(
+            format!("⚠️  {}", notice),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
+    if let Some(token) = &state.token {
+        lines.push(Line::from(vec![
+            Span::styled("Token: ", Style::default().fg(Color::Gray)),
+            Span::styled(token, Style::default().fg(Color::Yellow)),
+            Span::styled(
+                "  (other devices: scan the QR code or add ?token=)",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+    }
+
+    if let Some(ca_path) = &state.ca_path {
+        lines.push(Line::from(vec![
+            Span::styled("HTTPS CA: ", Style::default().fg(Color::Gray)),
+            Span::styled(ca_path, Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("  (trust it on devices, phones: {})", tls::ca_url(&state.server_url)),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+    }
+
     if let Some(file) = &state.file_changed {
         lines.push(Line::from(vec![
             Span::styled("Changed: ", Style::default().fg(Color::Gray)),
@@ -234,7 +430,7 @@ fn render_qr_code(f: &mut Frame, area: Rect, state: &AppState) {
     use qrcode::QrCode;
     use qrcode::render::unicode;
     
-    let url = format!("http://{}:{}", state.local_ip, state.port);
+    let url = state.qr_url();
     
     let mut lines = vec![
         Line::from(Span::styled(
@@ -264,13 +460,24 @@ fn render_qr_code(f: &mut Frame, area: Rect, state: &AppState) {
         }
     };
     
+    let address = &state.addresses[state.qr_address];
+    let title = if state.addresses.len() > 1 {
+        format!(
+            "📱 Mobile Access ({}, {}/{}, [A] next)",
+            address.interface,
+            state.qr_address + 1,
+            state.addresses.len()
+        )
+    } else {
+        format!("📱 Mobile Access ({})", address.interface)
+    };
     let qr_widget = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("📱 Mobile Access"));
+        .block(Block::default().borders(Borders::ALL).title(title));
     f.render_widget(qr_widget, area);
 }
 
 fn render_controls(f: &mut Frame, area: Rect) {
-    let controls = Paragraph::new("  [R] Rebuild  [B] Open Browser  [D] Production Build & Exit  [Q] Quit  [Ctrl+C] Force Exit")
+    let controls = Paragraph::new("  [R] Rebuild  [A] Next Address  [B] Open Browser  [D] Production Build & Exit  [Q] Quit  [Ctrl+C] Force Exit")
         .style(Style::default().fg(Color::DarkGray))
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(controls, area);